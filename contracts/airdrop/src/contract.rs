@@ -16,9 +16,16 @@ use services::airdrop::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
     MerkleRootResponse, MigrateMsg, QueryMsg,
 };
+use services::migration::{init_contract_version, migrate_contract, MigrationFn};
+use services::ownership::{execute_ownership_msg, may_load_pending_owner};
 use sha3::Digest;
 use std::convert::TryInto;
 
+pub(crate) const CONTRACT_NAME: &str = "crates.io:nexus-airdrop";
+pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// (version, migration) pairs of releases which change the storage layout, sorted by version
+const MIGRATIONS: &[(&str, MigrationFn)] = &[];
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -37,18 +44,34 @@ pub fn instantiate(
     let stage: u8 = 0;
     store_latest_stage(deps.storage, stage)?;
 
+    init_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
     match msg {
-        ExecuteMsg::UpdateConfig { owner } => update_config(deps, info, owner),
+        ExecuteMsg::Ownership { ownership_msg } => {
+            let owner = deps.api.addr_humanize(&read_config(deps.storage)?.owner)?;
+            Ok(execute_ownership_msg(
+                deps,
+                &env,
+                &info,
+                &owner,
+                ownership_msg,
+                |deps, new_owner| {
+                    let mut config = read_config(deps.storage)?;
+                    config.owner = deps.api.addr_canonicalize(new_owner.as_str())?;
+                    store_config(deps.storage, &config)
+                },
+            )?)
+        }
         ExecuteMsg::RegisterMerkleRoot { merkle_root } => {
             register_merkle_root(deps, info, merkle_root)
         }
@@ -60,25 +83,6 @@ pub fn execute(
     }
 }
 
-pub fn update_config(
-    deps: DepsMut,
-    info: MessageInfo,
-    owner: Option<String>,
-) -> Result<Response, ContractError> {
-    let mut config: Config = read_config(deps.storage)?;
-    if deps.api.addr_canonicalize(info.sender.as_str())? != config.owner {
-        return Err(ContractError::Unauthorized);
-    }
-
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(&owner)?;
-    }
-
-    store_config(deps.storage, &config)?;
-
-    Ok(Response::new().add_attribute("action", "update_config"))
-}
-
 pub fn register_merkle_root(
     deps: DepsMut,
     info: MessageInfo,
@@ -206,6 +210,7 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let state = read_config(deps.storage)?;
     let resp = ConfigResponse {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
+        pending_owner: may_load_pending_owner(deps.storage)?,
         psi_token: deps.api.addr_humanize(&state.psi_token)?.to_string(),
     };
 
@@ -236,6 +241,6 @@ pub fn query_is_claimed(deps: Deps, stage: u8, address: String) -> StdResult<IsC
 }
 
 #[entry_point]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    migrate_contract(deps, &env, CONTRACT_NAME, CONTRACT_VERSION, MIGRATIONS)
 }
@@ -1,4 +1,5 @@
 use cosmwasm_std::StdError;
+use services::ownership::OwnershipError;
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -20,4 +21,21 @@ pub enum ContractError {
 
     #[error("Unauthorized")]
     Unauthorized,
+
+    #[error("No ownership proposal")]
+    NoOwnershipProposal,
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired,
+}
+
+impl From<OwnershipError> for ContractError {
+    fn from(error: OwnershipError) -> Self {
+        match error {
+            OwnershipError::Std(error) => ContractError::Std(error),
+            OwnershipError::Unauthorized => ContractError::Unauthorized,
+            OwnershipError::NoOwnershipProposal => ContractError::NoOwnershipProposal,
+            OwnershipError::OwnershipProposalExpired => ContractError::OwnershipProposalExpired,
+        }
+    }
 }
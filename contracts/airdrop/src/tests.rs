@@ -1,12 +1,16 @@
 use crate::contract::{execute, instantiate, query};
 use crate::error::ContractError;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{attr, from_binary, to_binary, CosmosMsg, SubMsg, Uint128, WasmMsg};
+use cosmwasm_std::{
+    attr, from_binary, to_binary, Addr, CosmosMsg, StdError, SubMsg, Uint128, WasmMsg,
+};
 use cw20::Cw20ExecuteMsg;
 use services::airdrop::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, IsClaimedResponse, LatestStageResponse,
     MerkleRootResponse, QueryMsg,
 };
+use services::migration::init_contract_version;
+use services::ownership::{OwnershipMsg, PendingOwner};
 
 #[test]
 fn proper_initialization() {
@@ -34,7 +38,7 @@ fn proper_initialization() {
 }
 
 #[test]
-fn update_config() {
+fn update_owner() {
     let mut deps = mock_dependencies(&[]);
 
     let msg = InstantiateMsg {
@@ -45,23 +49,50 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    // update owner
+    // propose new owner
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner0001".to_string()),
+    let msg = ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::ProposeNewOwner {
+            owner: "owner0001".to_string(),
+            expires_in: None,
+        },
     };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
     assert_eq!(0, res.messages.len());
 
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!("owner0000", config.owner.as_str());
+    assert_eq!(
+        Some(PendingOwner {
+            owner: Addr::unchecked("owner0001"),
+            expires_at: None,
+        }),
+        config.pending_owner
+    );
+
+    // accept ownership
+    let info = mock_info("owner0001", &[]);
+    let msg = ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::AcceptOwnership {},
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
     // it worked, let's query the state
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config: ConfigResponse = from_binary(&res).unwrap();
     assert_eq!("owner0001", config.owner.as_str());
+    assert_eq!(None, config.pending_owner);
 
     // Unauthorzied err
     let info = mock_info("owner0000", &[]);
-    let msg = ExecuteMsg::UpdateConfig { owner: None };
+    let msg = ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::ProposeNewOwner {
+            owner: "owner0000".to_string(),
+            expires_in: None,
+        },
+    };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
     match res {
@@ -244,3 +275,27 @@ fn register_merkle_root() {
 //         ]
 //     );
 // }
+
+#[test]
+fn migrate_checks_contract_name() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        psi_token: "psi0000".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    crate::contract::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    crate::contract::migrate(deps.as_mut(), mock_env(), services::airdrop::MigrateMsg {}).unwrap();
+
+    init_contract_version(&mut deps.storage, "crates.io:nexus-other", "1.0.0").unwrap();
+    let res = crate::contract::migrate(deps.as_mut(), mock_env(), services::airdrop::MigrateMsg {});
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Can not migrate crates.io:nexus-other to crates.io:nexus-airdrop"
+        ),
+        _ => panic!("Must return migration error"),
+    }
+}
@@ -11,6 +11,12 @@ use cosmwasm_std::{
 };
 
 use services::community::{ExecuteMsg, GovernanceMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use services::migration::{init_contract_version, migrate_contract, MigrationFn};
+
+pub(crate) const CONTRACT_NAME: &str = "crates.io:nexus-community";
+pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// (version, migration) pairs of releases which change the storage layout, sorted by version
+const MIGRATIONS: &[(&str, MigrationFn)] = &[];
 
 #[entry_point]
 pub fn instantiate(
@@ -26,6 +32,8 @@ pub fn instantiate(
 
     store_config(deps.storage, &config)?;
 
+    init_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
 
@@ -64,6 +72,6 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
 }
 
 #[entry_point]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    migrate_contract(deps, &env, CONTRACT_NAME, CONTRACT_VERSION, MIGRATIONS)
 }
@@ -1,6 +1,8 @@
 use cosmwasm_std::testing::mock_dependencies;
 use cosmwasm_std::testing::{mock_env, mock_info};
+use cosmwasm_std::StdError;
 use services::community::InstantiateMsg;
+use services::migration::init_contract_version;
 
 use crate::state::{load_config, Config};
 
@@ -21,3 +23,36 @@ fn proper_initialization() {
     assert_eq!(msg.governance_contract_addr, config.governance_contract);
     assert_eq!(msg.psi_token_addr, config.psi_token);
 }
+
+#[test]
+fn migrate_checks_contract_name() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        governance_contract_addr: "addr0001".to_string(),
+        psi_token_addr: "addr0002".to_string(),
+    };
+    let info = mock_info("addr0000", &[]);
+    crate::contract::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    crate::contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        services::community::MigrateMsg {},
+    )
+    .unwrap();
+
+    init_contract_version(&mut deps.storage, "crates.io:nexus-other", "1.0.0").unwrap();
+    let res = crate::contract::migrate(
+        deps.as_mut(),
+        mock_env(),
+        services::community::MigrateMsg {},
+    );
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Can not migrate crates.io:nexus-other to crates.io:nexus-community"
+        ),
+        _ => panic!("Must return migration error"),
+    }
+}
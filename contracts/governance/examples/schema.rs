@@ -3,9 +3,12 @@ use std::env::current_dir;
 use std::fs::create_dir_all;
 
 use services::governance::{
-    AnyoneMsg, ConfigResponse, Cw20HookMsg, ExecuteMsg, GovernanceMsg, InstantiateMsg, MigrateMsg,
+    AnyoneMsg, ConfigResponse, Cw20HookMsg, DelegateResponse, DelegationResponse,
+    DelegatorsResponse, ExecuteMsg, GovernanceMsg, GuardianMsg, InstantiateMsg, MigrateMsg,
     PollCountResponse, PollExecuteMsg, PollResponse, PollStatus, PollsResponse, QueryMsg,
-    StakerResponse, StateResponse, VoteOption, VoterInfo, VotersResponse, VotersResponseItem,
+    SignedVote, StakerResponse, StakersResponse, StateResponse, TotalVotingPowerResponse,
+    UnbondingResponse, VoteNonceResponse, VoteOption, VoterInfo, VoterPollsResponse,
+    VoterStatsResponse, VotersResponse, VotersResponseItem, VotingPowerResponse,
 };
 
 fn main() {
@@ -18,6 +21,7 @@ fn main() {
     export_schema(&schema_for!(ExecuteMsg), &out_dir);
     export_schema(&schema_for!(AnyoneMsg), &out_dir);
     export_schema(&schema_for!(GovernanceMsg), &out_dir);
+    export_schema(&schema_for!(GuardianMsg), &out_dir);
     export_schema(&schema_for!(Cw20HookMsg), &out_dir);
     export_schema(&schema_for!(PollExecuteMsg), &out_dir);
     export_schema(&schema_for!(QueryMsg), &out_dir);
@@ -32,5 +36,16 @@ fn main() {
     export_schema(&schema_for!(VoterInfo), &out_dir);
     export_schema(&schema_for!(PollStatus), &out_dir);
     export_schema(&schema_for!(VoteOption), &out_dir);
+    export_schema(&schema_for!(DelegationResponse), &out_dir);
+    export_schema(&schema_for!(DelegatorsResponse), &out_dir);
+    export_schema(&schema_for!(DelegateResponse), &out_dir);
+    export_schema(&schema_for!(UnbondingResponse), &out_dir);
+    export_schema(&schema_for!(SignedVote), &out_dir);
+    export_schema(&schema_for!(VoteNonceResponse), &out_dir);
+    export_schema(&schema_for!(VoterPollsResponse), &out_dir);
+    export_schema(&schema_for!(VoterStatsResponse), &out_dir);
+    export_schema(&schema_for!(StakersResponse), &out_dir);
+    export_schema(&schema_for!(VotingPowerResponse), &out_dir);
+    export_schema(&schema_for!(TotalVotingPowerResponse), &out_dir);
     export_schema(&schema_for!(MigrateMsg), &out_dir);
 }
@@ -1,39 +1,57 @@
 use cosmwasm_std::{
-    to_binary, Addr, BlockInfo, CosmosMsg, Decimal, DepsMut, Env, MessageInfo, Response, StdError,
-    StdResult, Storage, SubMsg, Uint128, WasmMsg,
+    to_binary, to_vec, Addr, Binary, BlockInfo, ContractResult, CosmosMsg, Decimal, Deps, DepsMut,
+    Env, MessageInfo, Response, StdResult, Storage, SubMsg, SubMsgExecutionResponse, Uint128,
+    WasmMsg,
 };
 use services::governance::{
-    ExecuteMsg, PollExecuteMsg, PollMigrateMsg, PollStatus, VoteOption, VoterInfo, YourselfMsg,
+    ExecuteMsg, ForfeitedDepositPolicy, PollAction, PollCategory, PollCategoryParams,
+    PollExecuteMsg, PollExecutionMode, PollMessageKind, PollMessageResult, PollMessageStatus,
+    PollMigrateMsg, PollOption, PollStatus, SignedVote, UnbondingEntry, VoteOption, VoterInfo,
+    WinningRule, YourselfMsg,
 };
 
 use crate::{
-    contract::POLL_EXECUTE_REPLY_ID,
-    querier::query_token_balance,
+    contract::{POLL_EXECUTE_REPLY_ID, POLL_MESSAGE_REPLY_ID_OFFSET},
+    error::ContractError,
+    querier::{query_poll_voting_power, query_token_balance, share_to_balance},
     state::{
-        load_bank, load_config, load_poll, load_poll_voter, load_state, may_load_bank,
-        remove_poll_indexer, remove_poll_voter, store_bank, store_config, store_poll,
-        store_poll_indexer, store_poll_voter, store_state, store_tmp_poll_id, Config, ExecuteData,
-        MigrateData, Poll, TokenManager,
+        self, is_migrating, load_bank, load_config, load_delegated_share, load_locked_balance,
+        load_poll, load_poll_delegated_vote, load_poll_voter, load_poll_withheld_share,
+        load_staker_checkpoint_before, load_state, load_tmp_keeper, load_total_share_before,
+        load_unbonding, load_vote_nonce, may_load_bank, may_load_delegation,
+        may_load_delegator_override, prune_voter_locks, read_poll_voters, remove_delegation,
+        remove_poll_indexer, remove_poll_voter, remove_voter_lock, remove_voter_poll, store_bank,
+        store_config, store_delegated_share, store_delegation, store_delegator_override,
+        store_poll, store_poll_delegated_vote, store_poll_indexer, store_poll_voter,
+        store_poll_withheld_share, store_staker_checkpoint, store_state, store_tmp_keeper,
+        store_tmp_poll_id, store_total_share_checkpoint, store_unbonding, store_vote_nonce,
+        store_voter_lock, store_voter_poll, Config, ExecuteData, MigrateData, Poll, PollOptionData,
+        StakerCheckpoint, State, TokenManager,
     },
     utils,
 };
 use cw20::Cw20ExecuteMsg;
+use sha2::{Digest, Sha256};
 
+#[allow(clippy::too_many_arguments)]
 pub fn update_config(
     deps: DepsMut,
     mut current_config: Config,
-    owner: Option<String>,
     quorum: Option<Decimal>,
     threshold: Option<Decimal>,
     voting_period: Option<u64>,
     timelock_period: Option<u64>,
     proposal_deposit: Option<Uint128>,
     snapshot_period: Option<u64>,
-) -> StdResult<Response> {
-    if let Some(ref owner) = owner {
-        current_config.owner = deps.api.addr_validate(owner)?;
-    }
-
+    unbonding_period: Option<u64>,
+    expiration_period: Option<u64>,
+    max_execution_retries: Option<u64>,
+    keeper_reward: Option<Uint128>,
+    max_keeper_reward_per_poll: Option<Uint128>,
+    forfeited_deposit_policy: Option<ForfeitedDepositPolicy>,
+    rejected_deposit_refund: Option<Decimal>,
+    category_params: Option<Vec<PollCategoryParams>>,
+) -> Result<Response, ContractError> {
     if let Some(quorum) = quorum {
         current_config.quorum = quorum;
     }
@@ -58,47 +76,119 @@ pub fn update_config(
         current_config.snapshot_period = snapshot_period;
     }
 
+    if let Some(unbonding_period) = unbonding_period {
+        current_config.unbonding_period = unbonding_period;
+    }
+
+    if let Some(expiration_period) = expiration_period {
+        current_config.expiration_period = expiration_period;
+    }
+
+    if let Some(max_execution_retries) = max_execution_retries {
+        current_config.max_execution_retries = max_execution_retries;
+    }
+
+    if let Some(keeper_reward) = keeper_reward {
+        current_config.keeper_reward = keeper_reward;
+    }
+
+    if let Some(max_keeper_reward_per_poll) = max_keeper_reward_per_poll {
+        current_config.max_keeper_reward_per_poll = max_keeper_reward_per_poll;
+    }
+
+    if let Some(forfeited_deposit_policy) = forfeited_deposit_policy {
+        if let ForfeitedDepositPolicy::Community { ref contract } = forfeited_deposit_policy {
+            deps.api.addr_validate(contract)?;
+        }
+        current_config.forfeited_deposit_policy = forfeited_deposit_policy;
+    }
+
+    if let Some(rejected_deposit_refund) = rejected_deposit_refund {
+        utils::validate_deposit_refund(rejected_deposit_refund)?;
+        current_config.rejected_deposit_refund = rejected_deposit_refund;
+    }
+
+    if let Some(category_params) = category_params {
+        utils::validate_category_params(&category_params)?;
+        current_config.category_params = category_params;
+    }
+
     store_config(deps.storage, &current_config)?;
     Ok(Response::default())
 }
 
+pub fn update_guardian(
+    deps: DepsMut,
+    mut current_config: Config,
+    guardian: Option<String>,
+) -> Result<Response, ContractError> {
+    current_config.guardian = guardian
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    store_config(deps.storage, &current_config)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "update_guardian"),
+        (
+            "guardian",
+            &current_config
+                .guardian
+                .map_or_else(String::new, |addr| addr.to_string()),
+        ),
+    ]))
+}
+
 pub fn stake_voting_tokens(
     deps: DepsMut,
     env: Env,
     _info: MessageInfo,
-    config: &Config,
     sender: Addr,
+    recipient: Option<String>,
     amount: Uint128,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     if amount.is_zero() {
-        return Err(StdError::generic_err("Insufficient funds sent"));
+        return Err(ContractError::InsufficientFunds);
     }
 
-    let mut token_manager = load_bank(deps.storage, &sender)?;
+    let staker = match &recipient {
+        Some(recipient) => deps.api.addr_validate(recipient)?,
+        None => sender.clone(),
+    };
+    let mut token_manager = load_bank(deps.storage, &staker)?;
     let mut state = load_state(deps.storage)?;
 
-    // balance already increased, so subtract deposit amount
-    let psi_balance = query_token_balance(deps.as_ref(), &config.psi_token, &env.contract.address)?;
-    let total_balance = psi_balance.checked_sub(state.total_deposit + amount)?;
-
-    let share = if total_balance.is_zero() || state.total_share.is_zero() {
+    let share = if state.total_staked.is_zero() || state.total_share.is_zero() {
         amount
     } else {
-        amount.multiply_ratio(state.total_share, total_balance)
+        amount.multiply_ratio(state.total_share, state.total_staked)
     };
 
     token_manager.share += share;
     state.total_share += share;
+    state.total_staked += amount;
 
     store_state(deps.storage, &state)?;
-    store_bank(deps.storage, &sender, &token_manager)?;
+    store_bank(deps.storage, &staker, &token_manager)?;
 
-    Ok(Response::new().add_attributes(vec![
+    store_total_share_checkpoint(deps.storage, env.block.height, state.total_share)?;
+    checkpoint_staker(deps.storage, &staker, env.block.height)?;
+
+    if let Some(delegate) = may_load_delegation(deps.storage, &staker)? {
+        let delegated_share = load_delegated_share(deps.storage, &delegate)?;
+        store_delegated_share(deps.storage, &delegate, delegated_share + share)?;
+        checkpoint_staker(deps.storage, &delegate, env.block.height)?;
+    }
+
+    let mut response = Response::new().add_attributes(vec![
         ("action", "staking"),
         ("sender", &sender.to_string()),
         ("share", &share.to_string()),
         ("amount", &amount.to_string()),
-    ]))
+    ]);
+    if recipient.is_some() {
+        response = response.add_attribute("recipient", staker.as_str());
+    }
+    Ok(response)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -112,19 +202,15 @@ pub fn create_poll(
     link: Option<String>,
     execute_msgs: Option<Vec<PollExecuteMsg>>,
     migrate_msgs: Option<Vec<PollMigrateMsg>>,
-) -> StdResult<Response> {
+    execution_mode: Option<PollExecutionMode>,
+    category: Option<PollCategory>,
+    options: Option<Vec<PollOption>>,
+    winning_rule: Option<WinningRule>,
+) -> Result<Response, ContractError> {
     utils::validate_title(&title)?;
     utils::validate_description(&description)?;
     utils::validate_link(&link)?;
 
-    let config: Config = load_config(deps.storage)?;
-    if deposit_amount < config.proposal_deposit {
-        return Err(StdError::generic_err(format!(
-            "Must deposit more than {} token",
-            config.proposal_deposit
-        )));
-    }
-
     let mut state = load_state(deps.storage)?;
     let poll_id = state.poll_count + 1;
 
@@ -132,17 +218,20 @@ pub fn create_poll(
     state.poll_count += 1;
     state.total_deposit += deposit_amount;
 
-    let all_execute_data: Option<Vec<ExecuteData>> = match execute_msgs {
+    let all_execute_data = to_execute_data(deps.as_ref(), &env, execute_msgs)?;
+
+    let all_migrate_data: Option<Vec<MigrateData>> = match migrate_msgs {
         None => None,
         Some(empty_vec) if empty_vec.is_empty() => None,
         Some(msgs) => {
-            let res: StdResult<Vec<ExecuteData>> = msgs
+            let res: Result<Vec<MigrateData>, ContractError> = msgs
                 .into_iter()
                 .map(|msg| {
-                    Ok(ExecuteData {
+                    Ok(MigrateData {
                         order: msg.order,
                         contract: deps.api.addr_validate(&msg.contract)?,
                         msg: msg.msg,
+                        new_code_id: msg.new_code_id,
                     })
                 })
                 .collect();
@@ -150,38 +239,74 @@ pub fn create_poll(
         }
     };
 
-    let all_migrate_data: Option<Vec<MigrateData>> = match migrate_msgs {
-        None => None,
-        Some(empty_vec) if empty_vec.is_empty() => None,
-        Some(msgs) => {
-            let res: StdResult<Vec<MigrateData>> = msgs
+    let options: Vec<PollOptionData> = match options {
+        None => vec![],
+        Some(options) => {
+            if all_execute_data.is_some() || all_migrate_data.is_some() {
+                return Err(ContractError::PollOptionsWithMessages);
+            }
+            utils::validate_poll_options(&options)?;
+            options
                 .into_iter()
-                .map(|msg| {
-                    Ok(MigrateData {
-                        order: msg.order,
-                        contract: deps.api.addr_validate(&msg.contract)?,
-                        msg: msg.msg,
-                        new_code_id: msg.new_code_id,
+                .map(|option| {
+                    Ok(PollOptionData {
+                        name: option.name,
+                        execute_data: to_execute_data(deps.as_ref(), &env, option.execute_msgs)?,
+                        votes: Uint128::zero(),
                     })
                 })
-                .collect();
-            Some(res?)
+                .collect::<Result<_, ContractError>>()?
         }
     };
+    let winning_rule = winning_rule.unwrap_or_default();
+    utils::validate_winning_rule(&winning_rule)?;
+
+    // multi-choice poll category is defined by messages of all options
+    let category_execute_data = if options.is_empty() {
+        all_execute_data.clone()
+    } else {
+        Some(
+            options
+                .iter()
+                .flat_map(|option| option.execute_data.clone().unwrap_or_default())
+                .collect::<Vec<ExecuteData>>(),
+        )
+        .filter(|data| !data.is_empty())
+    };
+    let category = utils::poll_category(
+        category,
+        &category_execute_data,
+        &all_migrate_data,
+        &env.contract.address,
+    )?;
+    let params = load_config(deps.storage)?.poll_params(&category);
+    if deposit_amount < params.proposal_deposit {
+        return Err(ContractError::InsufficientDeposit(params.proposal_deposit));
+    }
 
     let current_time = get_time(&env.block);
     let new_poll = Poll {
         id: poll_id,
         creator: proposer,
         status: PollStatus::InProgress,
+        category,
         yes_votes: Uint128::zero(),
         no_votes: Uint128::zero(),
-        end_time: current_time + config.voting_period,
+        abstain_votes: Uint128::zero(),
+        options,
+        winning_rule,
+        winning_option: None,
+        start_height: env.block.height,
+        end_time: current_time + params.voting_period,
         title,
         description,
         link,
         execute_data: all_execute_data,
         migrate_data: all_migrate_data,
+        execution_mode: execution_mode.unwrap_or_default(),
+        execution_results: vec![],
+        execution_retries: 0,
+        keeper_reward_paid: Uint128::zero(),
         deposit_amount,
         total_balance_at_end_poll: None,
         staked_amount: None,
@@ -200,22 +325,77 @@ pub fn create_poll(
     ]))
 }
 
-pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
+fn to_execute_data(
+    deps: Deps,
+    env: &Env,
+    execute_msgs: Option<Vec<PollExecuteMsg>>,
+) -> Result<Option<Vec<ExecuteData>>, ContractError> {
+    match execute_msgs {
+        None => Ok(None),
+        Some(empty_vec) if empty_vec.is_empty() => Ok(None),
+        Some(msgs) => {
+            let res: Result<Vec<ExecuteData>, ContractError> = msgs
+                .into_iter()
+                .map(|msg| {
+                    let contract = deps.api.addr_validate(&msg.contract)?;
+                    if msg.msg.is_some() == msg.action.is_some() {
+                        return Err(ContractError::InvalidPollMessage);
+                    }
+                    // governance actions can only be executed by this contract on itself
+                    if matches!(msg.action, Some(PollAction::Governance { .. }))
+                        && contract != env.contract.address
+                    {
+                        return Err(ContractError::InvalidPollActionContract);
+                    }
+                    utils::validate_funds(&msg.funds)?;
+                    if let Some(PollAction::BankSend { amount }) = &msg.action {
+                        if amount.is_empty() || !msg.funds.is_empty() {
+                            return Err(ContractError::InvalidBankSend);
+                        }
+                        utils::validate_funds(amount)?;
+                    }
+
+                    Ok(ExecuteData {
+                        order: msg.order,
+                        contract,
+                        msg: msg.msg,
+                        action: msg.action,
+                        funds: msg.funds,
+                    })
+                })
+                .collect();
+            Some(res).transpose()
+        }
+    }
+}
+
+pub fn end_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
     let mut a_poll: Poll = load_poll(deps.storage, poll_id)?;
 
     if a_poll.status != PollStatus::InProgress {
-        return Err(StdError::generic_err("Poll is not in progress"));
+        return Err(ContractError::PollNotInProgress);
     }
 
     let current_time = get_time(&env.block);
     if a_poll.end_time > current_time {
-        return Err(StdError::generic_err("Voting period has not expired"));
+        return Err(ContractError::VotingPeriodNotExpired);
     }
 
     let no = a_poll.no_votes.u128();
     let yes = a_poll.yes_votes.u128();
+    let abstain = a_poll.abstain_votes.u128();
+    let options_votes: u128 = a_poll
+        .options
+        .iter()
+        .map(|option| option.votes.u128())
+        .sum();
 
-    let tallied_weight = yes + no;
+    let tallied_weight = yes + no + abstain + options_votes;
 
     let mut poll_status = PollStatus::Rejected;
     let mut rejected_reason = "";
@@ -225,30 +405,35 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
     let config = load_config(deps.storage)?;
     let mut state = load_state(deps.storage)?;
 
-    let (quorum, staked_weight) = if state.total_share.u128() == 0 {
-        (Decimal::zero(), Uint128::zero())
-    } else if let Some(staked_amount) = a_poll.staked_amount {
-        (
-            Decimal::from_ratio(tallied_weight, staked_amount),
-            staked_amount,
-        )
+    let staked_weight = match a_poll.staked_amount {
+        Some(staked_amount) => staked_amount,
+        None => query_poll_staked_amount(deps.as_ref(), &a_poll)?,
+    };
+    let quorum = if staked_weight.is_zero() {
+        Decimal::zero()
     } else {
-        let psi_balance =
-            query_token_balance(deps.as_ref(), &config.psi_token, &env.contract.address)?;
-        let staked_weight = psi_balance.checked_sub(state.total_deposit)?;
-
-        (
-            Decimal::from_ratio(tallied_weight, staked_weight),
-            staked_weight,
-        )
+        Decimal::from_ratio(tallied_weight, staked_weight)
     };
 
-    if tallied_weight == 0 || quorum < config.quorum {
+    let mut refund = Uint128::zero();
+    let params = config.poll_params(&a_poll.category);
+    if tallied_weight == 0 || quorum < params.quorum {
         // Quorum: More than quorum of the total staked tokens at the end of the voting
         // period need to have participated in the vote.
         rejected_reason = "Quorum not reached";
     } else {
-        if Decimal::from_ratio(yes, tallied_weight) > config.threshold {
+        if !a_poll.options.is_empty() {
+            // Multi-choice poll passes if there is a winning option, threshold is not used
+            if let Some(winning_option) =
+                utils::winning_option(&a_poll.options, &a_poll.winning_rule)
+            {
+                a_poll.winning_option = Some(winning_option);
+                poll_status = PollStatus::Passed;
+                passed = true;
+            } else {
+                rejected_reason = "No winning option";
+            }
+        } else if yes != 0 && Decimal::from_ratio(yes, yes + no) > params.threshold {
             //Threshold: More than 50% of the tokens that participated in the vote
             // (after excluding “Abstain” votes) need to have voted in favor of the proposal (“Yes”).
             poll_status = PollStatus::Passed;
@@ -257,14 +442,19 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
             rejected_reason = "Threshold not reached";
         }
 
-        // Refunds deposit only when quorum is reached
-        if !a_poll.deposit_amount.is_zero() {
+        // Refunds deposit only when quorum is reached, rejected poll can get a part of it
+        refund = if passed {
+            a_poll.deposit_amount
+        } else {
+            a_poll.deposit_amount * config.rejected_deposit_refund
+        };
+        if !refund.is_zero() {
             messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
                 contract_addr: config.psi_token.to_string(),
                 funds: vec![],
                 msg: to_binary(&Cw20ExecuteMsg::Transfer {
                     recipient: a_poll.creator.to_string(),
-                    amount: a_poll.deposit_amount,
+                    amount: refund,
                 })?,
             })))
         }
@@ -272,6 +462,10 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
 
     // Decrease total deposit amount
     state.total_deposit = state.total_deposit.checked_sub(a_poll.deposit_amount)?;
+    let forfeited = a_poll.deposit_amount - refund;
+    if let Some(msg) = forfeit_deposit(&config, &mut state, forfeited)? {
+        messages.push(SubMsg::new(msg));
+    }
     store_state(deps.storage, &state)?;
 
     // Update poll indexer
@@ -281,39 +475,156 @@ pub fn end_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
     // Update poll status
     a_poll.status = poll_status;
     a_poll.total_balance_at_end_poll = Some(staked_weight);
+    let keeper_reward = pay_keeper_reward(deps.storage, &config, &mut a_poll, &info.sender)?;
     store_poll(deps.storage, poll_id, &a_poll)?;
 
-    Ok(Response::new()
+    let mut response = Response::new()
         .add_submessages(messages)
         .add_attributes(vec![
             ("action", "end_poll"),
             ("poll_id", &poll_id.to_string()),
             ("rejected_reason", &rejected_reason.to_string()),
             ("passed", &passed.to_string()),
+        ]);
+    if !forfeited.is_zero() {
+        response = response.add_attribute("forfeited_deposit", forfeited.to_string());
+    }
+    if let Some(winning_option) = a_poll.winning_option {
+        response = response.add_attribute("winning_option", winning_option.to_string());
+    }
+
+    Ok(add_keeper_reward(response, keeper_reward))
+}
+
+// handles not refunded part of a deposit by the configured policy, the state is not stored
+fn forfeit_deposit(
+    config: &Config,
+    state: &mut State,
+    amount: Uint128,
+) -> Result<Option<CosmosMsg>, ContractError> {
+    if amount.is_zero() {
+        return Ok(None);
+    }
+
+    let msg = match &config.forfeited_deposit_policy {
+        ForfeitedDepositPolicy::Stakers => {
+            // without stakers tokens are left in the contract as before
+            if !state.total_share.is_zero() {
+                state.total_staked += amount;
+            }
+            return Ok(None);
+        }
+        ForfeitedDepositPolicy::Community { contract } => Cw20ExecuteMsg::Transfer {
+            recipient: contract.to_string(),
+            amount,
+        },
+        ForfeitedDepositPolicy::Burn => Cw20ExecuteMsg::Burn { amount },
+    };
+
+    Ok(Some(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.psi_token.to_string(),
+        funds: vec![],
+        msg: to_binary(&msg)?,
+    })))
+}
+
+/// Cancel a poll by its creator while nobody has voted and refund the deposit
+pub fn cancel_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    let mut a_poll: Poll = load_poll(deps.storage, poll_id)?;
+
+    if a_poll.status != PollStatus::InProgress {
+        return Err(ContractError::PollNotInProgress);
+    }
+
+    // poll without votes after the voting period is ended with the deposit forfeited
+    if get_time(&env.block) > a_poll.end_time {
+        return Err(ContractError::VotingPeriodExpired);
+    }
+
+    if a_poll.creator != info.sender {
+        return Err(ContractError::Unauthorized);
+    }
+
+    if !read_poll_voters(deps.storage, poll_id, None, Some(1), None)?.is_empty() {
+        return Err(ContractError::PollHasVotes);
+    }
+
+    let config = load_config(deps.storage)?;
+    let mut state = load_state(deps.storage)?;
+    state.total_deposit = state.total_deposit.checked_sub(a_poll.deposit_amount)?;
+    store_state(deps.storage, &state)?;
+
+    let mut messages: Vec<SubMsg> = vec![];
+    if !a_poll.deposit_amount.is_zero() {
+        messages.push(SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: config.psi_token.to_string(),
+            funds: vec![],
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: a_poll.creator.to_string(),
+                amount: a_poll.deposit_amount,
+            })?,
+        })))
+    }
+
+    remove_poll_indexer(deps.storage, &PollStatus::InProgress, poll_id);
+    store_poll_indexer(deps.storage, &PollStatus::Cancelled, poll_id)?;
+
+    a_poll.status = PollStatus::Cancelled;
+    store_poll(deps.storage, poll_id, &a_poll)?;
+
+    Ok(Response::new()
+        .add_submessages(messages)
+        .add_attributes(vec![
+            ("action", "cancel_poll"),
+            ("poll_id", &poll_id.to_string()),
+            ("refund", &a_poll.deposit_amount.to_string()),
         ]))
 }
 
 /// Execute a msgs of passed poll as one submsg to catch failures
-pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
+pub fn execute_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
     let config: Config = load_config(deps.storage)?;
-    let a_poll = load_poll(deps.storage, poll_id)?;
+    let mut a_poll = load_poll(deps.storage, poll_id)?;
 
     if !a_poll.contain_messages() {
-        return Err(StdError::generic_err(
-            "The poll does not have executable data",
-        ));
+        return Err(ContractError::NoExecutableData);
     }
 
-    if a_poll.status != PollStatus::Passed {
-        return Err(StdError::generic_err("Poll is not in passed status"));
+    match a_poll.status {
+        PollStatus::Passed => {}
+        PollStatus::Failed => {
+            if a_poll.execution_retries >= config.max_execution_retries {
+                return Err(ContractError::NoExecutionRetriesLeft);
+            }
+            a_poll.execution_retries += 1;
+        }
+        _ => return Err(ContractError::PollNotPassed),
     }
 
     let current_time = get_time(&env.block);
-    if a_poll.end_time + config.timelock_period > current_time {
-        return Err(StdError::generic_err("Timelock period has not expired"));
+    let timelock_period = config.poll_params(&a_poll.category).timelock_period;
+    if a_poll.end_time + timelock_period > current_time {
+        return Err(ContractError::TimelockNotExpired);
     }
 
+    if a_poll.end_time + timelock_period + config.expiration_period < current_time {
+        return Err(ContractError::ExecutionPeriodExpired);
+    }
+
+    // the keeper is paid in the submessage, so a failed execution pays nothing
+    store_poll(deps.storage, poll_id, &a_poll)?;
     store_tmp_poll_id(deps.storage, poll_id)?;
+    store_tmp_keeper(deps.storage, &info.sender)?;
 
     Ok(Response::new().add_submessage(SubMsg::reply_on_error(
         CosmosMsg::Wasm(WasmMsg::Execute {
@@ -327,50 +638,157 @@ pub fn execute_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response
     )))
 }
 
-pub fn execute_poll_messages(deps: DepsMut, poll_id: u64) -> StdResult<Response> {
-    let mut a_poll: Poll = load_poll(deps.storage, poll_id)?;
+// pays the keeper reward from the pool for a poll lifecycle message, the poll is not stored
+fn pay_keeper_reward(
+    storage: &mut dyn Storage,
+    config: &Config,
+    a_poll: &mut Poll,
+    keeper: &Addr,
+) -> Result<Option<(Uint128, CosmosMsg)>, ContractError> {
+    let mut state = load_state(storage)?;
+    let reward = config
+        .keeper_reward
+        .min(
+            config
+                .max_keeper_reward_per_poll
+                .saturating_sub(a_poll.keeper_reward_paid),
+        )
+        .min(state.keeper_reward_pool);
+    if reward.is_zero() {
+        return Ok(None);
+    }
 
-    remove_poll_indexer(deps.storage, &PollStatus::Passed, poll_id);
-    store_poll_indexer(deps.storage, &PollStatus::Executed, poll_id)?;
+    state.keeper_reward_pool -= reward;
+    store_state(storage, &state)?;
+    a_poll.keeper_reward_paid += reward;
+
+    let msg = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: config.psi_token.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: keeper.to_string(),
+            amount: reward,
+        })?,
+    });
+    Ok(Some((reward, msg)))
+}
 
-    a_poll.status = PollStatus::Executed;
-    store_poll(deps.storage, poll_id, &a_poll)?;
+fn add_keeper_reward(response: Response, keeper_reward: Option<(Uint128, CosmosMsg)>) -> Response {
+    match keeper_reward {
+        Some((reward, msg)) => response
+            .add_message(msg)
+            .add_attribute("keeper_reward", reward.to_string()),
+        None => response,
+    }
+}
 
-    let mut execute_messages: Vec<CosmosMsg> = vec![];
-    if let Some(mut all_exec_msgs) = a_poll.execute_data {
+/// Poll messages in execution order, execute messages go before migrations
+fn poll_messages(a_poll: &Poll) -> StdResult<Vec<(PollMessageResult, CosmosMsg)>> {
+    let mut messages = vec![];
+    if let Some(mut all_exec_msgs) = a_poll.executable_data().cloned() {
         all_exec_msgs.sort();
         for msg in all_exec_msgs {
-            execute_messages.push(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: msg.contract.to_string(),
-                msg: msg.msg,
-                funds: vec![],
-            }));
+            messages.push((
+                PollMessageResult {
+                    kind: PollMessageKind::Execute,
+                    order: msg.order,
+                    contract: msg.contract.to_string(),
+                    status: PollMessageStatus::Success,
+                },
+                msg.to_cosmos_msg()?,
+            ));
         }
     }
 
-    let mut migration_messages: Vec<CosmosMsg> = vec![];
-    if let Some(mut all_migrate_msgs) = a_poll.migrate_data {
+    if let Some(mut all_migrate_msgs) = a_poll.migrate_data.clone() {
         all_migrate_msgs.sort();
         for msg in all_migrate_msgs {
-            migration_messages.push(CosmosMsg::Wasm(WasmMsg::Migrate {
-                contract_addr: msg.contract.to_string(),
-                new_code_id: msg.new_code_id,
-                msg: msg.msg,
-            }));
+            messages.push((
+                PollMessageResult {
+                    kind: PollMessageKind::Migrate,
+                    order: msg.order,
+                    contract: msg.contract.to_string(),
+                    status: PollMessageStatus::Success,
+                },
+                CosmosMsg::Wasm(WasmMsg::Migrate {
+                    contract_addr: msg.contract.to_string(),
+                    new_code_id: msg.new_code_id,
+                    msg: msg.msg,
+                }),
+            ));
         }
     }
+    Ok(messages)
+}
 
-    Ok(Response::new()
-        .add_messages(execute_messages)
-        .add_messages(migration_messages)
+pub fn execute_poll_messages(deps: DepsMut, poll_id: u64) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage)?;
+    let mut a_poll: Poll = load_poll(deps.storage, poll_id)?;
+    let keeper = load_tmp_keeper(deps.storage)?;
+
+    // poll is either passed or failed and being retried
+    remove_poll_indexer(deps.storage, &a_poll.status, poll_id);
+    store_poll_indexer(deps.storage, &PollStatus::Executed, poll_id)?;
+
+    a_poll.status = PollStatus::Executed;
+    a_poll.execution_results = vec![];
+    let keeper_reward = pay_keeper_reward(deps.storage, &config, &mut a_poll, &keeper)?;
+    store_poll(deps.storage, poll_id, &a_poll)?;
+
+    // every message replies with its result, in AllOrNothing mode
+    // an error is not caught and reverts the whole poll execution
+    let messages: Vec<SubMsg> = poll_messages(&a_poll)?
+        .into_iter()
+        .enumerate()
+        .map(|(index, (_, msg))| {
+            let id = POLL_MESSAGE_REPLY_ID_OFFSET + index as u64;
+            match a_poll.execution_mode {
+                PollExecutionMode::AllOrNothing => SubMsg::reply_on_success(msg, id),
+                PollExecutionMode::BestEffort => SubMsg::reply_always(msg, id),
+            }
+        })
+        .collect();
+
+    let response = Response::new()
+        .add_submessages(messages)
         .add_attributes(vec![
             ("action", "execute_poll"),
             ("poll_id", &poll_id.to_string()),
-        ]))
+        ]);
+    Ok(add_keeper_reward(response, keeper_reward))
+}
+
+/// Store the result of a poll message
+pub fn store_poll_message_result(
+    deps: DepsMut,
+    poll_id: u64,
+    index: u64,
+    result: ContractResult<SubMsgExecutionResponse>,
+) -> Result<Response, ContractError> {
+    let mut a_poll: Poll = load_poll(deps.storage, poll_id)?;
+
+    let (mut message_result, _) = poll_messages(&a_poll)?
+        .into_iter()
+        .nth(index as usize)
+        .ok_or(ContractError::PollMessageNotFound)?;
+    if let ContractResult::Err(error) = result {
+        message_result.status = PollMessageStatus::Failed { error };
+    }
+    let success = message_result.status == PollMessageStatus::Success;
+
+    a_poll.execution_results.push(message_result);
+    store_poll(deps.storage, poll_id, &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "poll_message_result"),
+        ("poll_id", &poll_id.to_string()),
+        ("index", &index.to_string()),
+        ("success", &success.to_string()),
+    ]))
 }
 
 /// Set the status of a poll to Failed if execute_poll fails
-pub fn fail_poll(deps: DepsMut, poll_id: u64) -> StdResult<Response> {
+pub fn fail_poll(deps: DepsMut, poll_id: u64, error: String) -> Result<Response, ContractError> {
     let mut a_poll: Poll = load_poll(deps.storage, poll_id)?;
 
     //remove 'Passed' status cause update status Passed->Executed will be reverted in 'execute_poll_messages'
@@ -378,6 +796,15 @@ pub fn fail_poll(deps: DepsMut, poll_id: u64) -> StdResult<Response> {
     store_poll_indexer(deps.storage, &PollStatus::Failed, poll_id)?;
 
     a_poll.status = PollStatus::Failed;
+    a_poll.execution_results = poll_messages(&a_poll)?
+        .into_iter()
+        .map(|(mut message_result, _)| {
+            message_result.status = PollMessageStatus::Reverted {
+                error: error.clone(),
+            };
+            message_result
+        })
+        .collect();
     store_poll(deps.storage, poll_id, &a_poll)?;
 
     Ok(Response::new().add_attributes(vec![
@@ -386,41 +813,99 @@ pub fn fail_poll(deps: DepsMut, poll_id: u64) -> StdResult<Response> {
     ]))
 }
 
+/// Guardian can stop a passed poll or a failed one which can be retried before it is executed
+pub fn veto_poll(deps: DepsMut, poll_id: u64) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage)?;
+    let mut a_poll = load_poll(deps.storage, poll_id)?;
+
+    match a_poll.status {
+        PollStatus::Passed => {}
+        PollStatus::Failed if a_poll.execution_retries < config.max_execution_retries => {}
+        _ => return Err(ContractError::PollNotPassed),
+    }
+
+    remove_poll_indexer(deps.storage, &a_poll.status, poll_id);
+    store_poll_indexer(deps.storage, &PollStatus::Vetoed, poll_id)?;
+
+    a_poll.status = PollStatus::Vetoed;
+    store_poll(deps.storage, poll_id, &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "veto_poll"),
+        ("poll_id", &poll_id.to_string()),
+    ]))
+}
+
+/// Move a passed poll which was not executed during expiration period to Expired status
+pub fn expire_poll(deps: DepsMut, env: Env, poll_id: u64) -> Result<Response, ContractError> {
+    let config: Config = load_config(deps.storage)?;
+    let mut a_poll = load_poll(deps.storage, poll_id)?;
+
+    if !a_poll.contain_messages() {
+        return Err(ContractError::NoExecutableData);
+    }
+
+    if a_poll.status != PollStatus::Passed {
+        return Err(ContractError::PollNotPassed);
+    }
+
+    let current_time = get_time(&env.block);
+    let timelock_period = config.poll_params(&a_poll.category).timelock_period;
+    if a_poll.end_time + timelock_period + config.expiration_period >= current_time {
+        return Err(ContractError::ExpirationPeriodNotPassed);
+    }
+
+    remove_poll_indexer(deps.storage, &PollStatus::Passed, poll_id);
+    store_poll_indexer(deps.storage, &PollStatus::Expired, poll_id)?;
+
+    a_poll.status = PollStatus::Expired;
+    store_poll(deps.storage, poll_id, &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "expire_poll"),
+        ("poll_id", &poll_id.to_string()),
+    ]))
+}
+
 /// SnapshotPoll is used to take a snapshot of the staked amount for quorum calculation
-pub fn snapshot_poll(deps: DepsMut, env: Env, poll_id: u64) -> StdResult<Response> {
+pub fn snapshot_poll(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
     let config = load_config(deps.storage)?;
     let mut a_poll = load_poll(deps.storage, poll_id)?;
 
     if a_poll.status != PollStatus::InProgress {
-        return Err(StdError::generic_err("Poll is not in progress"));
+        return Err(ContractError::PollNotInProgress);
     }
 
     let current_time = get_time(&env.block);
     let time_to_end = a_poll.end_time - current_time;
 
     if time_to_end > config.snapshot_period {
-        return Err(StdError::generic_err("Cannot snapshot at this time"));
+        return Err(ContractError::SnapshotNotAllowed);
     }
 
     if a_poll.staked_amount.is_some() {
-        return Err(StdError::generic_err("Snapshot has already occurred"));
+        return Err(ContractError::SnapshotAlreadyOccurred);
     }
 
-    // store the current staked amount for quorum calculation
-    let state = load_state(deps.storage)?;
-
-    let psi_balance = query_token_balance(deps.as_ref(), &config.psi_token, &env.contract.address)?;
-    let staked_amount = psi_balance.checked_sub(state.total_deposit)?;
+    // store the staked amount for quorum calculation
+    let staked_amount = query_poll_staked_amount(deps.as_ref(), &a_poll)?;
 
     a_poll.staked_amount = Some(staked_amount);
 
+    let keeper_reward = pay_keeper_reward(deps.storage, &config, &mut a_poll, &info.sender)?;
     store_poll(deps.storage, poll_id, &a_poll)?;
 
-    Ok(Response::new().add_attributes(vec![
+    let response = Response::new().add_attributes(vec![
         ("action", "snapshot_poll"),
         ("poll_id", &poll_id.to_string()),
         ("staked_amount", &staked_amount.to_string()),
-    ]))
+    ]);
+    Ok(add_keeper_reward(response, keeper_reward))
 }
 
 pub fn cast_vote(
@@ -430,82 +915,281 @@ pub fn cast_vote(
     poll_id: u64,
     vote: VoteOption,
     amount: Uint128,
-) -> StdResult<Response> {
-    let config = load_config(deps.storage)?;
-    let state = load_state(deps.storage)?;
+) -> Result<Response, ContractError> {
+    let mut a_poll = load_poll_for_voting(deps.storage, &env, poll_id)?;
+
+    // Check the voter already has a vote on the poll
+    if load_poll_voter(deps.storage, poll_id, &info.sender).is_ok() {
+        return Err(ContractError::AlreadyVoted);
+    }
+
+    let vote_info = add_vote(deps, &env, &mut a_poll, &info.sender, vote, amount)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "cast_vote"),
+        ("poll_id", &poll_id.to_string()),
+        ("amount", &amount.to_string()),
+        ("voter", &info.sender.to_string()),
+        ("vote_option", &vote_info.vote.to_string()),
+    ]))
+}
+
+/// Cast vote on behalf of the voter who signed it, nonce protects from replaying the vote
+pub fn cast_vote_by_signature(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    public_key: Binary,
+    vote: SignedVote,
+    signature: Binary,
+) -> Result<Response, ContractError> {
+    if vote.chain_id != env.block.chain_id || vote.contract != env.contract.address.as_str() {
+        return Err(ContractError::VoteForAnotherContract);
+    }
+
+    let message_hash = Sha256::digest(&to_vec(&vote)?);
+    let verified = deps
+        .api
+        .secp256k1_verify(&message_hash, &signature, &public_key)
+        .map_err(|_| ContractError::InvalidVoteSignature)?;
+    if !verified {
+        return Err(ContractError::InvalidVoteSignature);
+    }
+
+    let voter = utils::address_from_public_key(deps.api, &public_key)?;
+    let nonce = load_vote_nonce(deps.storage, &voter)?;
+    if vote.nonce != nonce {
+        return Err(ContractError::InvalidVoteNonce);
+    }
+    store_vote_nonce(deps.storage, &voter, nonce + 1)?;
+
+    let voter_info = MessageInfo {
+        sender: voter,
+        funds: vec![],
+    };
+    let response = cast_vote(deps, env, voter_info, vote.poll_id, vote.vote, vote.amount)?;
+    Ok(response.add_attributes(vec![
+        ("relayer", info.sender.as_str()),
+        ("nonce", &nonce.to_string()),
+    ]))
+}
+
+/// Replace the voter's vote on the in-progress poll with a new one
+pub fn change_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+    vote: VoteOption,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    let mut a_poll = load_poll_for_voting(deps.storage, &env, poll_id)?;
+    let previous_vote = remove_vote(deps.storage, &env, &mut a_poll, &info.sender)?;
+    let vote_info = add_vote(deps, &env, &mut a_poll, &info.sender, vote, amount)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "change_vote"),
+        ("poll_id", &poll_id.to_string()),
+        ("amount", &amount.to_string()),
+        ("voter", info.sender.as_str()),
+        ("vote_option", &vote_info.vote.to_string()),
+        ("previous_amount", &previous_vote.balance.to_string()),
+        ("previous_vote_option", &previous_vote.vote.to_string()),
+    ]))
+}
+
+/// Remove the voter's vote from the in-progress poll and unlock his tokens
+pub fn retract_vote(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    poll_id: u64,
+) -> Result<Response, ContractError> {
+    let mut a_poll = load_poll_for_voting(deps.storage, &env, poll_id)?;
+    let previous_vote = remove_vote(deps.storage, &env, &mut a_poll, &info.sender)?;
+
+    let staked_amount = query_poll_staked_amount(deps.as_ref(), &a_poll)?;
+    process_snapshot(deps.storage, &env, &mut a_poll, staked_amount)?;
+    store_poll(deps.storage, poll_id, &a_poll)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "retract_vote"),
+        ("poll_id", &poll_id.to_string()),
+        ("amount", &previous_vote.balance.to_string()),
+        ("voter", info.sender.as_str()),
+        ("vote_option", &previous_vote.vote.to_string()),
+    ]))
+}
+
+fn load_poll_for_voting(
+    storage: &dyn Storage,
+    env: &Env,
+    poll_id: u64,
+) -> Result<Poll, ContractError> {
+    let state = load_state(storage)?;
     if poll_id == 0 || state.poll_count < poll_id {
-        return Err(StdError::generic_err("Poll does not exist"));
+        return Err(ContractError::PollNotFound);
     }
 
-    let mut a_poll = load_poll(deps.storage, poll_id)?;
+    let a_poll = load_poll(storage, poll_id)?;
     let current_time = get_time(&env.block);
     if a_poll.status != PollStatus::InProgress || current_time > a_poll.end_time {
-        return Err(StdError::generic_err("Poll is not in progress"));
+        return Err(ContractError::PollNotInProgress);
     }
 
-    // Check the voter already has a vote on the poll
-    if load_poll_voter(deps.storage, poll_id, &info.sender).is_ok() {
-        return Err(StdError::generic_err("User has already voted."));
+    Ok(a_poll)
+}
+
+// counts the vote in poll tally, locks voter tokens and stores the poll.
+// Voting power is taken from the voter checkpoint made before the poll creation block.
+fn add_vote(
+    deps: DepsMut,
+    env: &Env,
+    a_poll: &mut Poll,
+    voter: &Addr,
+    vote: VoteOption,
+    amount: Uint128,
+) -> Result<VoterInfo, ContractError> {
+    let poll_id = a_poll.id;
+    if !a_poll.is_valid_vote(&vote) {
+        return Err(ContractError::InvalidVoteOption);
+    }
+    if let VoteOption::Split { allocations } = &vote {
+        let total_weight = allocations
+            .iter()
+            .fold(Decimal::zero(), |total, allocation| {
+                total + allocation.weight
+            });
+        if total_weight != Decimal::one() {
+            return Err(ContractError::InvalidSplitWeights);
+        }
     }
 
-    let mut token_manager = load_bank(deps.storage, &info.sender)?;
+    let (own_balance, delegated_balance) = query_poll_voting_power(deps.as_ref(), a_poll, voter)?;
+    let checkpoint = load_staker_checkpoint_before(deps.storage, voter, a_poll.start_height)?;
 
-    // convert share to amount
-    let total_share = state.total_share;
-    let psi_balance = query_token_balance(deps.as_ref(), &config.psi_token, &env.contract.address)?;
-    let total_balance = psi_balance.checked_sub(state.total_deposit)?;
+    if own_balance + delegated_balance < amount {
+        return Err(ContractError::InsufficientStake);
+    }
 
-    if token_manager
-        .share
-        .multiply_ratio(total_balance, total_share)
-        < amount
-    {
-        return Err(StdError::generic_err(
-            "User does not have enough staked tokens.",
-        ));
+    // voting by yourself overrides your delegate vote on this poll,
+    // override stays in place even if the vote is retracted later
+    if let Some(delegate) = checkpoint.delegate {
+        if may_load_delegator_override(deps.storage, voter, poll_id)?.is_none() {
+            let withheld_share = load_poll_withheld_share(deps.storage, poll_id, &delegate)?;
+            store_poll_withheld_share(
+                deps.storage,
+                poll_id,
+                &delegate,
+                withheld_share + checkpoint.share,
+            )?;
+            store_delegator_override(deps.storage, voter, poll_id, checkpoint.share)?;
+
+            let checkpoint_balance = share_to_balance(deps.as_ref(), checkpoint.share)?;
+            revoke_delegated_vote(deps.storage, a_poll, &delegate, checkpoint_balance)?;
+        }
     }
 
     // update tally info
-    if VoteOption::Yes == vote {
-        a_poll.yes_votes += amount;
-    } else {
-        a_poll.no_votes += amount;
-    }
+    a_poll.add_votes(&vote, amount);
 
+    // only own tokens are locked, delegated part is tracked separately
+    let own_amount = std::cmp::min(amount, own_balance);
     let vote_info = VoterInfo {
         vote,
         balance: amount,
     };
-    token_manager
-        .locked_balance
-        .push((poll_id, vote_info.clone()));
-    store_bank(deps.storage, &info.sender, &token_manager)?;
+    store_voter_lock(
+        deps.storage,
+        voter,
+        a_poll.end_time,
+        poll_id,
+        &VoterInfo {
+            vote: vote_info.vote.clone(),
+            balance: own_amount,
+        },
+    )?;
+    store_poll_delegated_vote(deps.storage, poll_id, voter, amount - own_amount)?;
 
     // store poll voter && and update poll data
-    store_poll_voter(deps.storage, poll_id, &info.sender, &vote_info)?;
+    store_poll_voter(deps.storage, poll_id, voter, &vote_info)?;
+    store_voter_poll(deps.storage, voter, poll_id, &vote_info)?;
 
-    // processing snapshot
-    let time_to_end = a_poll.end_time - current_time;
+    let staked_amount = query_poll_staked_amount(deps.as_ref(), a_poll)?;
+    process_snapshot(deps.storage, env, a_poll, staked_amount)?;
+    store_poll(deps.storage, poll_id, a_poll)?;
+
+    Ok(vote_info)
+}
+
+// removes the vote from poll tally and unlocks voter tokens, poll is not stored
+fn remove_vote(
+    storage: &mut dyn Storage,
+    env: &Env,
+    a_poll: &mut Poll,
+    voter: &Addr,
+) -> Result<VoterInfo, ContractError> {
+    let poll_id = a_poll.id;
+    let vote_info = match load_poll_voter(storage, poll_id, voter) {
+        Ok(vote_info) => vote_info,
+        Err(_) => return Err(ContractError::NotVoted),
+    };
+
+    a_poll.remove_votes(&vote_info.vote, vote_info.balance)?;
+
+    remove_voter_lock(
+        storage,
+        voter,
+        a_poll.end_time,
+        poll_id,
+        get_time(&env.block),
+    )?;
+
+    store_poll_delegated_vote(storage, poll_id, voter, Uint128::zero())?;
+    remove_poll_voter(storage, poll_id, voter);
+    remove_voter_poll(storage, voter, poll_id)?;
+
+    Ok(vote_info)
+}
+
+// the first vote in snapshot period fixes staked amount for quorum calculation
+fn process_snapshot(
+    storage: &dyn Storage,
+    env: &Env,
+    a_poll: &mut Poll,
+    staked_amount: Uint128,
+) -> Result<(), ContractError> {
+    let config = load_config(storage)?;
+    let time_to_end = a_poll.end_time - get_time(&env.block);
 
     if time_to_end < config.snapshot_period && a_poll.staked_amount.is_none() {
-        a_poll.staked_amount = Some(total_balance);
+        a_poll.staked_amount = Some(staked_amount);
     }
 
-    store_poll(deps.storage, poll_id, &a_poll)?;
+    Ok(())
+}
 
-    Ok(Response::new().add_attributes(vec![
-        ("action", "cast_vote"),
-        ("poll_id", &poll_id.to_string()),
-        ("amount", &amount.to_string()),
-        ("voter", &info.sender.to_string()),
-        ("vote_option", &vote_info.vote.to_string()),
-    ]))
+/// returns the amount of PSI tokens staked before the poll creation block
+fn query_poll_staked_amount(deps: Deps, a_poll: &Poll) -> StdResult<Uint128> {
+    let total_share = load_total_share_before(deps.storage, a_poll.start_height)?;
+    share_to_balance(deps, total_share)
+}
+
+// stores current staker voting power, should be called after every change
+// of staker share, delegated share or delegation
+fn checkpoint_staker(storage: &mut dyn Storage, staker: &Addr, height: u64) -> StdResult<()> {
+    let checkpoint = StakerCheckpoint {
+        share: load_bank(storage, staker)?.share,
+        delegated_share: load_delegated_share(storage, staker)?,
+        delegate: may_load_delegation(storage, staker)?,
+    };
+    store_staker_checkpoint(storage, staker, height, &checkpoint)
 }
 
-pub fn register_token(deps: DepsMut, psi_token: String) -> StdResult<Response> {
+pub fn register_token(deps: DepsMut, psi_token: String) -> Result<Response, ContractError> {
     let mut config: Config = load_config(deps.storage)?;
     if config.psi_token != "" {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized);
     }
 
     config.psi_token = deps.api.addr_validate(&psi_token)?;
@@ -520,88 +1204,426 @@ pub fn withdraw_voting_tokens(
     env: Env,
     info: MessageInfo,
     amount: Option<Uint128>,
-) -> StdResult<Response> {
-    if let Some(mut token_manager) = may_load_bank(deps.storage, &info.sender)? {
+) -> Result<Response, ContractError> {
+    // nothing can be withdrawn by stakers who left, total staked can be zero then
+    let token_manager = may_load_bank(deps.storage, &info.sender)?
+        .filter(|token_manager| !token_manager.share.is_zero());
+    if let Some(mut token_manager) = token_manager {
         let config: Config = load_config(deps.storage)?;
         let mut state = load_state(deps.storage)?;
         let user_address = info.sender;
 
-        // Load total share & total balance except proposal deposit amount
         let total_share = state.total_share.u128();
-        let psi_balance =
-            query_token_balance(deps.as_ref(), &config.psi_token, &env.contract.address)?;
-        let total_balance = psi_balance.checked_sub(state.total_deposit)?.u128();
+        let total_balance = state.total_staked.u128();
 
-        let locked_balance =
-            compute_locked_balance(deps.storage, &mut token_manager, &user_address)?;
+        let current_time = get_time(&env.block);
+        prune_voter_locks(deps.storage, &user_address, current_time)?;
+        let locked_balance = load_locked_balance(deps.storage, &user_address, current_time)?.u128();
         let locked_share = locked_balance * total_share / total_balance;
         let user_share = token_manager.share.u128();
 
+        let unlocked_share = user_share
+            .checked_sub(locked_share)
+            .ok_or(ContractError::WithdrawTooMany)?;
         let withdraw_share = amount
             .map(|v| std::cmp::max(v.multiply_ratio(total_share, total_balance).u128(), 1u128))
-            .unwrap_or_else(|| user_share - locked_share);
+            .unwrap_or(unlocked_share);
         let withdraw_amount = amount
             .map(|v| v.u128())
             .unwrap_or_else(|| withdraw_share * total_balance / total_share);
 
-        if locked_share + withdraw_share > user_share {
-            Err(StdError::generic_err(
-                "User is trying to withdraw too many tokens.",
-            ))
+        if withdraw_share > unlocked_share {
+            Err(ContractError::WithdrawTooMany)
         } else {
             let share = user_share - withdraw_share;
             token_manager.share = Uint128::from(share);
 
             store_bank(deps.storage, &user_address, &token_manager)?;
+            checkpoint_staker(deps.storage, &user_address, env.block.height)?;
+
+            if let Some(delegate) = may_load_delegation(deps.storage, &user_address)? {
+                let delegated_share = load_delegated_share(deps.storage, &delegate)?;
+                store_delegated_share(
+                    deps.storage,
+                    &delegate,
+                    delegated_share.checked_sub(Uint128::new(withdraw_share))?,
+                )?;
+                checkpoint_staker(deps.storage, &delegate, env.block.height)?;
+            }
 
             state.total_share = Uint128::from(total_share - withdraw_share);
+            state.total_staked = state
+                .total_staked
+                .checked_sub(Uint128::new(withdraw_amount))?;
+
+            if config.unbonding_period == 0 {
+                store_state(deps.storage, &state)?;
+                store_total_share_checkpoint(deps.storage, env.block.height, state.total_share)?;
+
+                return Ok(Response::new()
+                    .add_message(WasmMsg::Execute {
+                        contract_addr: config.psi_token.to_string(),
+                        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                            recipient: user_address.to_string(),
+                            amount: Uint128::new(withdraw_amount),
+                        })?,
+                        funds: vec![],
+                    })
+                    .add_attributes(vec![
+                        ("action", "withdraw"),
+                        ("recipient", &user_address.to_string()),
+                        ("amount", &withdraw_amount.to_string()),
+                    ]));
+            }
+
+            // tokens stay on the contract till the end of unbonding period
+            let release_time = get_time(&env.block) + config.unbonding_period;
+            let mut unbonding = load_unbonding(deps.storage, &user_address)?;
+            unbonding.push(UnbondingEntry {
+                amount: Uint128::new(withdraw_amount),
+                release_time,
+            });
+            store_unbonding(deps.storage, &user_address, &unbonding)?;
+
+            state.total_unbonding += Uint128::new(withdraw_amount);
             store_state(deps.storage, &state)?;
-
-            Ok(Response::new()
-                .add_message(WasmMsg::Execute {
-                    contract_addr: config.psi_token.to_string(),
-                    msg: to_binary(&Cw20ExecuteMsg::Transfer {
-                        recipient: user_address.to_string(),
-                        amount: Uint128::new(withdraw_amount),
-                    })?,
-                    funds: vec![],
-                })
-                .add_attributes(vec![
-                    ("action", "withdraw"),
-                    ("recipient", &user_address.to_string()),
-                    ("amount", &withdraw_amount.to_string()),
-                ]))
+            store_total_share_checkpoint(deps.storage, env.block.height, state.total_share)?;
+
+            Ok(Response::new().add_attributes(vec![
+                ("action", "withdraw"),
+                ("recipient", user_address.as_str()),
+                ("amount", &withdraw_amount.to_string()),
+                ("release_time", &release_time.to_string()),
+            ]))
         }
     } else {
-        Err(StdError::generic_err("Nothing staked"))
+        Err(ContractError::NothingStaked)
     }
 }
 
-// removes not in-progress poll voter info & unlock tokens
-// and returns the largest locked amount in participated polls.
-fn compute_locked_balance(
-    storage: &mut dyn Storage,
-    token_manager: &mut TokenManager,
-    voter: &Addr,
-) -> StdResult<u128> {
-    // filter out not in-progress polls
-    token_manager.locked_balance.retain(|(poll_id, _)| {
-        let poll: Poll = load_poll(storage, *poll_id).unwrap();
-
-        if poll.status != PollStatus::InProgress {
-            // remove voter info from the poll
-            remove_poll_voter(storage, *poll_id, &voter);
-        }
+/// Move staked tokens which are not locked in polls to another staker,
+/// voting power of both stakers changes only in polls created afterwards.
+pub fn transfer_stake(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    recipient: String,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InsufficientFunds);
+    }
 
-        poll.status == PollStatus::InProgress
-    });
+    let recipient = deps.api.addr_validate(&recipient)?;
+    if recipient == info.sender {
+        return Err(ContractError::SelfTransfer);
+    }
+
+    let mut token_manager = match may_load_bank(deps.storage, &info.sender)? {
+        Some(token_manager) if !token_manager.share.is_zero() => token_manager,
+        _ => return Err(ContractError::NothingStaked),
+    };
+
+    let state = load_state(deps.storage)?;
+    let total_share = state.total_share.u128();
+    let total_balance = state.total_staked.u128();
+
+    let current_time = get_time(&env.block);
+    prune_voter_locks(deps.storage, &info.sender, current_time)?;
+    let locked_balance = load_locked_balance(deps.storage, &info.sender, current_time)?.u128();
+    let locked_share = locked_balance * total_share / total_balance;
+    let unlocked_share = token_manager
+        .share
+        .u128()
+        .checked_sub(locked_share)
+        .ok_or(ContractError::WithdrawTooMany)?;
+    let transfer_share = std::cmp::max(
+        amount.multiply_ratio(total_share, total_balance).u128(),
+        1u128,
+    );
+
+    if transfer_share > unlocked_share {
+        return Err(ContractError::WithdrawTooMany);
+    }
+
+    let transfer_share = Uint128::new(transfer_share);
+    token_manager.share = token_manager.share.checked_sub(transfer_share)?;
+    store_bank(deps.storage, &info.sender, &token_manager)?;
+
+    let mut recipient_token_manager = load_bank(deps.storage, &recipient)?;
+    recipient_token_manager.share += transfer_share;
+    store_bank(deps.storage, &recipient, &recipient_token_manager)?;
+
+    if let Some(delegate) = may_load_delegation(deps.storage, &info.sender)? {
+        let delegated_share = load_delegated_share(deps.storage, &delegate)?;
+        store_delegated_share(
+            deps.storage,
+            &delegate,
+            delegated_share.checked_sub(transfer_share)?,
+        )?;
+        checkpoint_staker(deps.storage, &delegate, env.block.height)?;
+    }
+    if let Some(delegate) = may_load_delegation(deps.storage, &recipient)? {
+        let delegated_share = load_delegated_share(deps.storage, &delegate)?;
+        store_delegated_share(deps.storage, &delegate, delegated_share + transfer_share)?;
+        checkpoint_staker(deps.storage, &delegate, env.block.height)?;
+    }
+
+    checkpoint_staker(deps.storage, &info.sender, env.block.height)?;
+    checkpoint_staker(deps.storage, &recipient, env.block.height)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "transfer_stake"),
+        ("sender", info.sender.as_str()),
+        ("recipient", recipient.as_str()),
+        ("share", &transfer_share.to_string()),
+        ("amount", &amount.to_string()),
+    ]))
+}
+
+/// Add PSI tokens transferred to the contract to the staked amount as rewards
+pub fn add_rewards(deps: DepsMut, env: Env, amount: Uint128) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InsufficientFunds);
+    }
+
+    let config = load_config(deps.storage)?;
+    let mut state = load_state(deps.storage)?;
+    if state.total_share.is_zero() {
+        return Err(ContractError::NothingStaked);
+    }
+
+    let free_balance = query_free_balance(deps.as_ref(), &env, &config, &state)?;
+    if free_balance < amount {
+        return Err(ContractError::InsufficientFreeBalance(free_balance));
+    }
+
+    state.total_staked += amount;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "add_rewards"),
+        ("amount", &amount.to_string()),
+        ("total_staked", &state.total_staked.to_string()),
+    ]))
+}
+
+/// Add transferred PSI tokens to the pool which pays keeper rewards
+pub fn add_keeper_rewards(
+    deps: DepsMut,
+    env: Env,
+    amount: Uint128,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InsufficientFunds);
+    }
+
+    let config = load_config(deps.storage)?;
+    let mut state = load_state(deps.storage)?;
+    let free_balance = query_free_balance(deps.as_ref(), &env, &config, &state)?;
+    if free_balance < amount {
+        return Err(ContractError::InsufficientFreeBalance(free_balance));
+    }
+
+    state.keeper_reward_pool += amount;
+    store_state(deps.storage, &state)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "add_keeper_rewards"),
+        ("amount", &amount.to_string()),
+        ("keeper_reward_pool", &state.keeper_reward_pool.to_string()),
+    ]))
+}
+
+// only tokens which are not staked, deposited, unbonding or reserved for keepers are free
+fn query_free_balance(
+    deps: Deps,
+    env: &Env,
+    config: &Config,
+    state: &State,
+) -> Result<Uint128, ContractError> {
+    let psi_balance = query_token_balance(deps, &config.psi_token, &env.contract.address)?;
+    Ok(psi_balance.saturating_sub(
+        state.total_staked + state.total_deposit + state.total_unbonding + state.keeper_reward_pool,
+    ))
+}
+
+/// Transfer unbonding tokens which release time has passed
+pub fn claim_unbonded(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+) -> Result<Response, ContractError> {
+    let current_time = get_time(&env.block);
+    let (released, unbonding): (Vec<UnbondingEntry>, Vec<UnbondingEntry>) =
+        load_unbonding(deps.storage, &info.sender)?
+            .into_iter()
+            .partition(|entry| entry.release_time <= current_time);
 
-    Ok(token_manager
-        .locked_balance
+    let amount = released
         .iter()
-        .map(|(_, v)| v.balance.u128())
-        .max()
-        .unwrap_or_default())
+        .fold(Uint128::zero(), |sum, entry| sum + entry.amount);
+    if amount.is_zero() {
+        return Err(ContractError::NothingToClaim);
+    }
+
+    store_unbonding(deps.storage, &info.sender, &unbonding)?;
+
+    let mut state = load_state(deps.storage)?;
+    state.total_unbonding = state.total_unbonding.checked_sub(amount)?;
+    store_state(deps.storage, &state)?;
+
+    let config = load_config(deps.storage)?;
+    Ok(Response::new()
+        .add_message(WasmMsg::Execute {
+            contract_addr: config.psi_token.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: info.sender.to_string(),
+                amount,
+            })?,
+            funds: vec![],
+        })
+        .add_attributes(vec![
+            ("action", "claim_unbonded"),
+            ("recipient", info.sender.as_str()),
+            ("amount", &amount.to_string()),
+        ]))
+}
+
+pub fn delegate(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    delegate: String,
+) -> Result<Response, ContractError> {
+    let delegate = deps.api.addr_validate(&delegate)?;
+    if delegate == info.sender {
+        return Err(ContractError::SelfDelegation);
+    }
+
+    let token_manager = match may_load_bank(deps.storage, &info.sender)? {
+        Some(token_manager) if !token_manager.share.is_zero() => token_manager,
+        _ => return Err(ContractError::NothingStaked),
+    };
+
+    if let Some(current_delegate) = may_load_delegation(deps.storage, &info.sender)? {
+        if current_delegate == delegate {
+            return Err(ContractError::AlreadyDelegated);
+        }
+
+        remove_delegate(
+            deps.storage,
+            &env,
+            &info.sender,
+            &token_manager,
+            &current_delegate,
+        )?;
+    }
+
+    // delegation affects only polls created after this block,
+    // so votes in already created polls are not changed
+    store_delegation(deps.storage, &info.sender, &delegate)?;
+    let delegated_share = load_delegated_share(deps.storage, &delegate)?;
+    store_delegated_share(
+        deps.storage,
+        &delegate,
+        delegated_share + token_manager.share,
+    )?;
+    checkpoint_staker(deps.storage, &info.sender, env.block.height)?;
+    checkpoint_staker(deps.storage, &delegate, env.block.height)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "delegate"),
+        ("delegator", info.sender.as_str()),
+        ("delegate", delegate.as_str()),
+        ("share", &token_manager.share.to_string()),
+    ]))
+}
+
+pub fn undelegate(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let delegate = match may_load_delegation(deps.storage, &info.sender)? {
+        Some(delegate) => delegate,
+        None => return Err(ContractError::NothingDelegated),
+    };
+
+    let token_manager = load_bank(deps.storage, &info.sender)?;
+    remove_delegate(deps.storage, &env, &info.sender, &token_manager, &delegate)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "undelegate"),
+        ("delegator", info.sender.as_str()),
+        ("delegate", delegate.as_str()),
+        ("share", &token_manager.share.to_string()),
+    ]))
+}
+
+/// Migrates a batch of polls and stakers stored before 1.1.0, so the migration
+/// of a contract with many stakers fits into the block gas limit
+pub fn migrate_batch(deps: DepsMut, env: Env, limit: u32) -> Result<Response, ContractError> {
+    if !is_migrating(deps.storage)? {
+        return Err(ContractError::NothingToMigrate);
+    }
+
+    let (migrated, finished) = state::migrate_batch(
+        deps.storage,
+        limit as usize,
+        |execute_data, migrate_data| {
+            utils::poll_category(None, execute_data, migrate_data, &env.contract.address)
+                .unwrap_or(PollCategory::Text)
+        },
+    )?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "migrate_batch"),
+        ("migrated", &migrated.to_string()),
+        ("finished", &finished.to_string()),
+    ]))
+}
+
+// takes delegated tokens back from the delegate,
+// votes in already created polls are not changed
+fn remove_delegate(
+    storage: &mut dyn Storage,
+    env: &Env,
+    delegator: &Addr,
+    token_manager: &TokenManager,
+    delegate: &Addr,
+) -> Result<(), ContractError> {
+    let delegated_share = load_delegated_share(storage, delegate)?;
+    store_delegated_share(
+        storage,
+        delegate,
+        delegated_share.checked_sub(token_manager.share)?,
+    )?;
+    remove_delegation(storage, delegator, delegate);
+
+    checkpoint_staker(storage, delegator, env.block.height)?;
+    checkpoint_staker(storage, delegate, env.block.height)?;
+    Ok(())
+}
+
+// delegate vote can be decreased only by the part made with delegated tokens
+fn revoke_delegated_vote(
+    storage: &mut dyn Storage,
+    poll: &mut Poll,
+    delegate: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    let delegated_vote = load_poll_delegated_vote(storage, poll.id, delegate)?;
+    if delegated_vote.is_zero() {
+        return Ok(());
+    }
+
+    let revoked = std::cmp::min(delegated_vote, amount);
+    let mut vote_info = load_poll_voter(storage, poll.id, delegate)?;
+    // split vote is recounted for the whole balance to keep its rounding consistent
+    poll.remove_votes(&vote_info.vote, vote_info.balance)?;
+    vote_info.balance = vote_info.balance.checked_sub(revoked)?;
+    poll.add_votes(&vote_info.vote, vote_info.balance);
+
+    store_poll_voter(storage, poll.id, delegate, &vote_info)?;
+    store_voter_poll(storage, delegate, poll.id, &vote_info)?;
+    store_poll_delegated_vote(storage, poll.id, delegate, delegated_vote - revoked)
 }
 
 fn get_time(block: &BlockInfo) -> u64 {
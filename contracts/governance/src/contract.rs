@@ -1,19 +1,27 @@
 use crate::{
-    commands, queries,
-    state::{load_config, load_tmp_poll_id, store_config, store_state, Config, State},
+    commands,
+    error::ContractError,
+    querier::query_token_balance,
+    queries,
+    state::{
+        is_migrating, load_config, load_tmp_poll_id, migrate_config, migrate_state,
+        start_migration, store_config, store_state, Config, State,
+    },
     utils,
 };
 
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply,
-    Response, StdError, StdResult, Uint128,
+    entry_point, from_binary, to_binary, Addr, Binary, Decimal, Deps, DepsMut, Env, MessageInfo,
+    Reply, Response, StdResult, Uint128,
 };
 
 use cw20::Cw20ReceiveMsg;
 use services::governance::{
-    AnyoneMsg, Cw20HookMsg, ExecuteMsg, GovernanceMsg, InstantiateMsg, MigrateMsg, QueryMsg,
-    YourselfMsg,
+    AnyoneMsg, Cw20HookMsg, ExecuteMsg, ForfeitedDepositPolicy, GovernanceMsg, GuardianMsg,
+    InstantiateMsg, MigrateMsg, QueryMsg, YourselfMsg,
 };
+use services::migration::{init_contract_version, migrate_contract, MigrationFn};
+use services::ownership::execute_ownership_msg;
 
 pub(crate) const MIN_TITLE_LENGTH: usize = 4;
 pub(crate) const MAX_TITLE_LENGTH: usize = 64;
@@ -21,8 +29,21 @@ pub(crate) const MIN_DESC_LENGTH: usize = 4;
 pub(crate) const MAX_DESC_LENGTH: usize = 1024;
 pub(crate) const MIN_LINK_LENGTH: usize = 12;
 pub(crate) const MAX_LINK_LENGTH: usize = 128;
+pub(crate) const MIN_OPTION_NAME_LENGTH: usize = 1;
+pub(crate) const MAX_OPTION_NAME_LENGTH: usize = 64;
+pub(crate) const MIN_POLL_OPTIONS: usize = 2;
+pub(crate) const MAX_POLL_OPTIONS: usize = 10;
+pub(crate) const MIN_DENOM_LENGTH: usize = 3;
+pub(crate) const MAX_DENOM_LENGTH: usize = 128;
 
 pub(crate) const POLL_EXECUTE_REPLY_ID: u64 = 1;
+/// Reply id of a poll message is this offset plus the message index
+pub(crate) const POLL_MESSAGE_REPLY_ID_OFFSET: u64 = 100;
+
+pub(crate) const CONTRACT_NAME: &str = "crates.io:nexus-governance";
+pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// (version, migration) pairs of releases which change the storage layout, sorted by version
+const MIGRATIONS: &[(&str, MigrationFn)] = &[("1.1.0", migrate_1_1_0)];
 
 #[entry_point]
 pub fn instantiate(
@@ -30,9 +51,10 @@ pub fn instantiate(
     _env: Env,
     info: MessageInfo,
     msg: InstantiateMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     utils::validate_quorum(msg.quorum)?;
     utils::validate_threshold(msg.threshold)?;
+    utils::validate_category_params(&msg.category_params)?;
 
     let config = Config {
         psi_token: Addr::unchecked(""),
@@ -43,63 +65,152 @@ pub fn instantiate(
         timelock_period: msg.timelock_period,
         proposal_deposit: msg.proposal_deposit,
         snapshot_period: msg.snapshot_period,
+        unbonding_period: msg.unbonding_period,
+        expiration_period: msg.expiration_period,
+        max_execution_retries: 0,
+        keeper_reward: Uint128::zero(),
+        max_keeper_reward_per_poll: Uint128::zero(),
+        forfeited_deposit_policy: ForfeitedDepositPolicy::Stakers,
+        rejected_deposit_refund: Decimal::one(),
+        guardian: None,
+        category_params: msg.category_params,
     };
 
     let state = State {
         poll_count: 0,
         total_share: Uint128::zero(),
+        total_staked: Uint128::zero(),
         total_deposit: Uint128::zero(),
+        total_unbonding: Uint128::zero(),
+        keeper_reward_pool: Uint128::zero(),
     };
 
     store_config(deps.storage, &config)?;
     store_state(deps.storage, &state)?;
 
+    init_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> StdResult<Response> {
+pub fn reply(deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
     match msg.id {
         POLL_EXECUTE_REPLY_ID => {
             let poll_id: u64 = load_tmp_poll_id(deps.storage)?;
-            commands::fail_poll(deps, poll_id)
+            let error = msg.result.into_result().err().unwrap_or_default();
+            commands::fail_poll(deps, poll_id, error)
         }
-        _ => Err(StdError::generic_err("reply id is invalid")),
+        id if id >= POLL_MESSAGE_REPLY_ID_OFFSET => {
+            let poll_id: u64 = load_tmp_poll_id(deps.storage)?;
+            commands::store_poll_message_result(
+                deps,
+                poll_id,
+                id - POLL_MESSAGE_REPLY_ID_OFFSET,
+                msg.result,
+            )
+        }
+        _ => Err(ContractError::InvalidReplyId),
     }
 }
 
 #[entry_point]
-pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> StdResult<Response> {
+pub fn execute(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msg: ExecuteMsg,
+) -> Result<Response, ContractError> {
+    // nothing else can be done till all entries stored before 1.1.0 are migrated
+    if is_migrating(deps.storage)?
+        && !matches!(
+            msg,
+            ExecuteMsg::Anyone {
+                anyone_msg: AnyoneMsg::MigrateBatch { .. }
+            }
+        )
+    {
+        return Err(ContractError::MigrationInProgress);
+    }
+
     match msg {
         ExecuteMsg::Governance { governance_msg } => {
             let config: Config = load_config(deps.storage)?;
             if info.sender != config.owner {
-                return Err(StdError::generic_err("unauthorized"));
+                return Err(ContractError::Unauthorized);
             }
 
             match governance_msg {
                 GovernanceMsg::UpdateConfig {
-                    owner,
                     quorum,
                     threshold,
                     voting_period,
                     timelock_period,
                     proposal_deposit,
                     snapshot_period,
+                    unbonding_period,
+                    expiration_period,
+                    max_execution_retries,
+                    keeper_reward,
+                    max_keeper_reward_per_poll,
+                    forfeited_deposit_policy,
+                    rejected_deposit_refund,
+                    category_params,
                 } => commands::update_config(
                     deps,
                     config,
-                    owner,
                     quorum,
                     threshold,
                     voting_period,
                     timelock_period,
                     proposal_deposit,
                     snapshot_period,
+                    unbonding_period,
+                    expiration_period,
+                    max_execution_retries,
+                    keeper_reward,
+                    max_keeper_reward_per_poll,
+                    forfeited_deposit_policy,
+                    rejected_deposit_refund,
+                    category_params,
                 ),
+                GovernanceMsg::AddRewards { amount } => commands::add_rewards(deps, env, amount),
+                GovernanceMsg::AddKeeperRewards { amount } => {
+                    commands::add_keeper_rewards(deps, env, amount)
+                }
+                GovernanceMsg::UpdateGuardian { guardian } => {
+                    commands::update_guardian(deps, config, guardian)
+                }
             }
         }
 
+        ExecuteMsg::Guardian { guardian_msg } => {
+            let config: Config = load_config(deps.storage)?;
+            if config.guardian != Some(info.sender) {
+                return Err(ContractError::Unauthorized);
+            }
+
+            match guardian_msg {
+                GuardianMsg::VetoPoll { poll_id } => commands::veto_poll(deps, poll_id),
+            }
+        }
+
+        ExecuteMsg::Ownership { ownership_msg } => {
+            let config: Config = load_config(deps.storage)?;
+            Ok(execute_ownership_msg(
+                deps,
+                &env,
+                &info,
+                &config.owner,
+                ownership_msg,
+                |deps, new_owner| {
+                    let mut config = load_config(deps.storage)?;
+                    config.owner = new_owner.clone();
+                    store_config(deps.storage, &config)
+                },
+            )?)
+        }
+
         ExecuteMsg::Receive(msg) => receive_cw20(deps, env, info, msg),
 
         ExecuteMsg::Anyone { anyone_msg } => match anyone_msg {
@@ -107,19 +218,41 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             AnyoneMsg::WithdrawVotingTokens { amount } => {
                 commands::withdraw_voting_tokens(deps, env, info, amount)
             }
+            AnyoneMsg::TransferStake { recipient, amount } => {
+                commands::transfer_stake(deps, env, info, recipient, amount)
+            }
+            AnyoneMsg::ClaimUnbonded {} => commands::claim_unbonded(deps, env, info),
             AnyoneMsg::CastVote {
                 poll_id,
                 vote,
                 amount,
             } => commands::cast_vote(deps, env, info, poll_id, vote, amount),
-            AnyoneMsg::EndPoll { poll_id } => commands::end_poll(deps, env, poll_id),
-            AnyoneMsg::ExecutePoll { poll_id } => commands::execute_poll(deps, env, poll_id),
-            AnyoneMsg::SnapshotPoll { poll_id } => commands::snapshot_poll(deps, env, poll_id),
+            AnyoneMsg::CastVoteBySignature {
+                public_key,
+                vote,
+                signature,
+            } => commands::cast_vote_by_signature(deps, env, info, public_key, vote, signature),
+            AnyoneMsg::ChangeVote {
+                poll_id,
+                vote,
+                amount,
+            } => commands::change_vote(deps, env, info, poll_id, vote, amount),
+            AnyoneMsg::RetractVote { poll_id } => commands::retract_vote(deps, env, info, poll_id),
+            AnyoneMsg::EndPoll { poll_id } => commands::end_poll(deps, env, info, poll_id),
+            AnyoneMsg::ExecutePoll { poll_id } => commands::execute_poll(deps, env, info, poll_id),
+            AnyoneMsg::CancelPoll { poll_id } => commands::cancel_poll(deps, env, info, poll_id),
+            AnyoneMsg::ExpirePoll { poll_id } => commands::expire_poll(deps, env, poll_id),
+            AnyoneMsg::SnapshotPoll { poll_id } => {
+                commands::snapshot_poll(deps, env, info, poll_id)
+            }
+            AnyoneMsg::Delegate { delegate } => commands::delegate(deps, env, info, delegate),
+            AnyoneMsg::Undelegate {} => commands::undelegate(deps, env, info),
+            AnyoneMsg::MigrateBatch { limit } => commands::migrate_batch(deps, env, limit),
         },
 
         ExecuteMsg::Yourself { yourself_msg } => {
             if info.sender != env.contract.address {
-                return Err(StdError::generic_err("unauthorized"));
+                return Err(ContractError::Unauthorized);
             }
 
             match yourself_msg {
@@ -136,17 +269,17 @@ pub fn receive_cw20(
     env: Env,
     info: MessageInfo,
     cw20_msg: Cw20ReceiveMsg,
-) -> StdResult<Response> {
+) -> Result<Response, ContractError> {
     // only asset contract can execute this message
     let config: Config = load_config(deps.storage)?;
     if config.psi_token != info.sender {
-        return Err(StdError::generic_err("unauthorized"));
+        return Err(ContractError::Unauthorized);
     }
 
     let real_sender = Addr::unchecked(cw20_msg.sender);
     match from_binary(&cw20_msg.msg) {
-        Ok(Cw20HookMsg::StakeVotingTokens {}) => {
-            commands::stake_voting_tokens(deps, env, info, &config, real_sender, cw20_msg.amount)
+        Ok(Cw20HookMsg::StakeVotingTokens { recipient }) => {
+            commands::stake_voting_tokens(deps, env, info, real_sender, recipient, cw20_msg.amount)
         }
         Ok(Cw20HookMsg::CreatePoll {
             title,
@@ -154,6 +287,10 @@ pub fn receive_cw20(
             link,
             execute_msgs,
             migrate_msgs,
+            execution_mode,
+            category,
+            options,
+            winning_rule,
         }) => commands::create_poll(
             deps,
             env,
@@ -164,9 +301,13 @@ pub fn receive_cw20(
             link,
             execute_msgs,
             migrate_msgs,
+            execution_mode,
+            category,
+            options,
+            winning_rule,
         ),
 
-        Err(err) => Err(err),
+        Err(err) => Err(ContractError::Std(err)),
     }
 }
 
@@ -201,10 +342,64 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             limit,
             order_by,
         )?),
+        QueryMsg::Delegation { address } => to_binary(&queries::query_delegation(deps, address)?),
+        QueryMsg::Delegators {
+            delegate,
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&queries::query_delegators(
+            deps,
+            delegate,
+            start_after,
+            limit,
+            order_by,
+        )?),
+        QueryMsg::Delegate { address } => to_binary(&queries::query_delegate(deps, address)?),
+        QueryMsg::Unbonding { address } => to_binary(&queries::query_unbonding(deps, address)?),
+        QueryMsg::VoteNonce { address } => to_binary(&queries::query_vote_nonce(deps, address)?),
+        QueryMsg::VoterPolls {
+            address,
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&queries::query_voter_polls(
+            deps,
+            address,
+            start_after,
+            limit,
+            order_by,
+        )?),
+        QueryMsg::VoterStats { address } => to_binary(&queries::query_voter_stats(deps, address)?),
+        QueryMsg::Stakers {
+            start_after,
+            limit,
+            order_by,
+        } => to_binary(&queries::query_stakers(deps, start_after, limit, order_by)?),
+        QueryMsg::VotingPower { address, poll_id } => {
+            to_binary(&queries::query_voting_power(deps, address, poll_id)?)
+        }
+        QueryMsg::TotalVotingPower { at } => {
+            to_binary(&queries::query_total_voting_power(deps, at)?)
+        }
     }
 }
 
+// polls and stakers are migrated in batches by AnyoneMsg::MigrateBatch
+fn migrate_1_1_0(deps: DepsMut, env: &Env) -> StdResult<()> {
+    let config = migrate_config(deps.storage)?;
+    let psi_balance = query_token_balance(deps.as_ref(), &config.psi_token, &env.contract.address)?;
+    migrate_state(deps.storage, psi_balance)?;
+    start_migration(deps.storage, env.block.height)
+}
+
 #[entry_point]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    Ok(migrate_contract(
+        deps,
+        &env,
+        CONTRACT_NAME,
+        CONTRACT_VERSION,
+        MIGRATIONS,
+    )?)
 }
@@ -0,0 +1,206 @@
+use cosmwasm_std::{OverflowError, StdError, Uint128};
+use services::ownership::OwnershipError;
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("{0}")]
+    Overflow(#[from] OverflowError),
+
+    #[error("unauthorized")]
+    Unauthorized,
+
+    #[error("No ownership proposal")]
+    NoOwnershipProposal,
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired,
+
+    #[error("reply id is invalid")]
+    InvalidReplyId,
+
+    #[error("Insufficient funds sent")]
+    InsufficientFunds,
+
+    #[error("Contract has only {0} free tokens")]
+    InsufficientFreeBalance(Uint128),
+
+    #[error("Must deposit more than {0} token")]
+    InsufficientDeposit(Uint128),
+
+    #[error("Title too short")]
+    TitleTooShort,
+
+    #[error("Title too long")]
+    TitleTooLong,
+
+    #[error("Description too short")]
+    DescriptionTooShort,
+
+    #[error("Description too long")]
+    DescriptionTooLong,
+
+    #[error("Link too short")]
+    LinkTooShort,
+
+    #[error("Link too long")]
+    LinkTooLong,
+
+    #[error("Too few poll options")]
+    TooFewPollOptions,
+
+    #[error("Too many poll options")]
+    TooManyPollOptions,
+
+    #[error("Duplicated poll option")]
+    DuplicatedPollOption,
+
+    #[error("Option name too short")]
+    OptionNameTooShort,
+
+    #[error("Option name too long")]
+    OptionNameTooLong,
+
+    #[error("Poll options can not be combined with poll messages")]
+    PollOptionsWithMessages,
+
+    #[error("Duplicated poll category")]
+    DuplicatedPollCategory,
+
+    #[error("Poll category does not match poll messages")]
+    PollCategoryMismatch,
+
+    #[error("quorum must be 0 to 1")]
+    InvalidQuorum,
+
+    #[error("threshold must be 0 to 1")]
+    InvalidThreshold,
+
+    #[error("share must be 0 to 1")]
+    InvalidWinningShare,
+
+    #[error("deposit refund must be 0 to 1")]
+    InvalidDepositRefund,
+
+    #[error("Poll does not exist")]
+    PollNotFound,
+
+    #[error("Poll is not in progress")]
+    PollNotInProgress,
+
+    #[error("Poll is not in passed status")]
+    PollNotPassed,
+
+    #[error("Poll already has votes")]
+    PollHasVotes,
+
+    #[error("Voting period has not expired")]
+    VotingPeriodNotExpired,
+
+    #[error("Voting period has expired")]
+    VotingPeriodExpired,
+
+    #[error("Timelock period has not expired")]
+    TimelockNotExpired,
+
+    #[error("Poll execution period has expired")]
+    ExecutionPeriodExpired,
+
+    #[error("Expiration period has not passed")]
+    ExpirationPeriodNotPassed,
+
+    #[error("The poll does not have executable data")]
+    NoExecutableData,
+
+    #[error("Poll has no execution retries left")]
+    NoExecutionRetriesLeft,
+
+    #[error("Poll message must have either msg or action")]
+    InvalidPollMessage,
+
+    #[error("Governance action must be executed on this contract")]
+    InvalidPollActionContract,
+
+    #[error("Invalid funds: {0}")]
+    InvalidFunds(String),
+
+    #[error("Bank send must have an amount and no funds")]
+    InvalidBankSend,
+
+    #[error("Poll message does not exist")]
+    PollMessageNotFound,
+
+    #[error("Cannot snapshot at this time")]
+    SnapshotNotAllowed,
+
+    #[error("Snapshot has already occurred")]
+    SnapshotAlreadyOccurred,
+
+    #[error("User has already voted.")]
+    AlreadyVoted,
+
+    #[error("User has not voted.")]
+    NotVoted,
+
+    #[error("Invalid vote option")]
+    InvalidVoteOption,
+
+    #[error("Split vote weights must sum to 1")]
+    InvalidSplitWeights,
+
+    #[error("User does not have enough staked tokens.")]
+    InsufficientStake,
+
+    #[error("Public key must be compressed")]
+    UncompressedPublicKey,
+
+    #[error("Vote is signed for another contract")]
+    VoteForAnotherContract,
+
+    #[error("Invalid vote signature")]
+    InvalidVoteSignature,
+
+    #[error("Invalid vote nonce")]
+    InvalidVoteNonce,
+
+    #[error("Nothing staked")]
+    NothingStaked,
+
+    #[error("User is trying to withdraw too many tokens.")]
+    WithdrawTooMany,
+
+    #[error("Cannot transfer stake to yourself")]
+    SelfTransfer,
+
+    #[error("Nothing to claim")]
+    NothingToClaim,
+
+    #[error("Cannot delegate to yourself")]
+    SelfDelegation,
+
+    #[error("Already delegated to this address")]
+    AlreadyDelegated,
+
+    #[error("Nothing delegated")]
+    NothingDelegated,
+
+    #[error("Migration of stored entries is in progress")]
+    MigrationInProgress,
+
+    #[error("Nothing to migrate")]
+    NothingToMigrate,
+}
+
+impl From<OwnershipError> for ContractError {
+    fn from(error: OwnershipError) -> Self {
+        match error {
+            OwnershipError::Std(error) => ContractError::Std(error),
+            OwnershipError::Unauthorized => ContractError::Unauthorized,
+            OwnershipError::NoOwnershipProposal => ContractError::NoOwnershipProposal,
+            OwnershipError::OwnershipProposalExpired => ContractError::OwnershipProposalExpired,
+        }
+    }
+}
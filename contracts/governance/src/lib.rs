@@ -1,5 +1,6 @@
 pub mod commands;
 pub mod contract;
+pub mod error;
 pub mod querier;
 pub mod queries;
 pub mod state;
@@ -1,27 +1,54 @@
-use cosmwasm_std::{Addr, Binary, Deps, QueryRequest, StdResult, Uint128, WasmQuery};
-use cosmwasm_storage::to_length_prefixed;
+use cosmwasm_std::{Addr, Deps, StdResult, Uint128};
+use cw20::{BalanceResponse, Cw20QueryMsg};
+
+use crate::state::{
+    load_bank, load_delegated_share, load_poll_withheld_share, load_staker_checkpoint_before,
+    load_state, Poll,
+};
+use std::cmp::min;
 
 pub fn query_token_balance(
     deps: Deps,
     contract_addr: &Addr,
     account_addr: &Addr,
 ) -> StdResult<Uint128> {
-    // load balance form the cw20 token contract version 0.6+
-    Ok(deps
-        .querier
-        .query(&QueryRequest::Wasm(WasmQuery::Raw {
-            contract_addr: contract_addr.to_string(),
-            key: Binary::from(concat(
-                &to_length_prefixed(b"balance"),
-                account_addr.as_bytes(),
-            )),
-        }))
-        .unwrap_or_else(|_| Uint128::zero()))
+    let response: BalanceResponse = deps.querier.query_wasm_smart(
+        contract_addr,
+        &Cw20QueryMsg::Balance {
+            address: account_addr.to_string(),
+        },
+    )?;
+    Ok(response.balance)
+}
+
+/// converts staked share to the amount of PSI tokens
+pub fn share_to_balance(deps: Deps, share: Uint128) -> StdResult<Uint128> {
+    let state = load_state(deps.storage)?;
+    if state.total_share.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    Ok(share.multiply_ratio(state.total_staked, state.total_share))
 }
 
-#[inline]
-fn concat(namespace: &[u8], key: &[u8]) -> Vec<u8> {
-    let mut k = namespace.to_vec();
-    k.extend_from_slice(key);
-    k
+/// returns own and delegated voting power of the voter in the poll,
+/// taken from the voter checkpoint made before the poll creation block.
+/// Power is capped by the current stake, tokens moved since then can not vote
+pub fn query_poll_voting_power(
+    deps: Deps,
+    poll: &Poll,
+    voter: &Addr,
+) -> StdResult<(Uint128, Uint128)> {
+    let checkpoint = load_staker_checkpoint_before(deps.storage, voter, poll.start_height)?;
+    let own_share = min(checkpoint.share, load_bank(deps.storage, voter)?.share);
+    let own_balance = share_to_balance(deps, own_share)?;
+    // delegators who already voted by themselves are excluded from delegated power
+    let delegated_share = min(
+        checkpoint.delegated_share,
+        load_delegated_share(deps.storage, voter)?,
+    )
+    .saturating_sub(load_poll_withheld_share(deps.storage, poll.id, voter)?);
+    let delegated_balance = share_to_balance(deps, delegated_share)?;
+
+    Ok((own_balance, delegated_balance))
 }
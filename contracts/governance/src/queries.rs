@@ -1,17 +1,24 @@
-use cosmwasm_std::{Deps, Env, StdError, StdResult, Uint128};
+use cosmwasm_std::{Deps, Env, StdError, StdResult};
 use services::{
     common::OrderBy,
     governance::{
-        ConfigResponse, PollExecuteMsg, PollMigrateMsg, PollResponse, PollStatus, PollsResponse,
-        StakerResponse, StateResponse, VotersResponse, VotersResponseItem,
+        ConfigResponse, DelegateResponse, DelegationResponse, DelegatorsResponse,
+        DelegatorsResponseItem, PollExecuteMsg, PollMigrateMsg, PollOptionResponse, PollResponse,
+        PollStatus, PollsResponse, StakerResponse, StakersResponse, StakersResponseItem,
+        StateResponse, TotalVotingPowerResponse, UnbondingResponse, VoteNonceResponse,
+        VoterPollsResponse, VoterPollsResponseItem, VoterStatsResponse, VotersResponse,
+        VotersResponseItem, VotingPowerResponse,
     },
+    ownership::may_load_pending_owner,
 };
 
 use crate::{
-    querier::query_token_balance,
+    querier::{query_poll_voting_power, share_to_balance},
     state::{
-        load_bank, load_config, load_poll, load_state, may_load_poll, read_poll_voters, read_polls,
-        Config, Poll,
+        load_bank, load_config, load_delegated_share, load_poll, load_state,
+        load_total_share_before, load_unbonding, load_vote_nonce, load_voter_stats,
+        may_load_delegation, may_load_poll, read_delegators, read_poll_voters, read_polls,
+        read_stakers, read_voter_poll_locks, read_voter_polls, Config, PollOptionData,
     },
 };
 
@@ -19,6 +26,7 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let config: Config = load_config(deps.storage)?;
     Ok(ConfigResponse {
         owner: config.owner.to_string(),
+        pending_owner: may_load_pending_owner(deps.storage)?,
         psi_token: config.psi_token.to_string(),
         quorum: config.quorum,
         threshold: config.threshold,
@@ -26,6 +34,15 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
         timelock_period: config.timelock_period,
         proposal_deposit: config.proposal_deposit,
         snapshot_period: config.snapshot_period,
+        unbonding_period: config.unbonding_period,
+        expiration_period: config.expiration_period,
+        max_execution_retries: config.max_execution_retries,
+        keeper_reward: config.keeper_reward,
+        max_keeper_reward_per_poll: config.max_keeper_reward_per_poll,
+        forfeited_deposit_policy: config.forfeited_deposit_policy,
+        rejected_deposit_refund: config.rejected_deposit_refund,
+        guardian: config.guardian.map(|addr| addr.to_string()),
+        category_params: config.category_params,
     })
 }
 
@@ -34,7 +51,10 @@ pub fn query_state(deps: Deps) -> StdResult<StateResponse> {
     Ok(StateResponse {
         poll_count: state.poll_count,
         total_share: state.total_share,
+        total_staked: state.total_staked,
         total_deposit: state.total_deposit,
+        total_unbonding: state.total_unbonding,
+        keeper_reward_pool: state.keeper_reward_pool,
     })
 }
 
@@ -48,6 +68,8 @@ pub fn query_poll(deps: Deps, poll_id: u64) -> StdResult<PollResponse> {
                     order: msg.order,
                     contract: msg.contract.to_string(),
                     msg: msg.msg.clone(),
+                    action: msg.action.clone(),
+                    funds: msg.funds.clone(),
                 })
                 .collect()
         });
@@ -68,6 +90,8 @@ pub fn query_poll(deps: Deps, poll_id: u64) -> StdResult<PollResponse> {
             id: poll.id,
             creator: poll.creator.to_string(),
             status: poll.status,
+            category: poll.category,
+            start_height: poll.start_height,
             end_time: poll.end_time,
             title: poll.title,
             description: poll.description,
@@ -75,8 +99,16 @@ pub fn query_poll(deps: Deps, poll_id: u64) -> StdResult<PollResponse> {
             deposit_amount: poll.deposit_amount,
             execute_data: execute_messages,
             migrate_data: migrate_messages,
+            execution_mode: poll.execution_mode,
+            execution_results: poll.execution_results,
+            execution_retries: poll.execution_retries,
+            keeper_reward_paid: poll.keeper_reward_paid,
             yes_votes: poll.yes_votes,
             no_votes: poll.no_votes,
+            abstain_votes: poll.abstain_votes,
+            options: poll_options_response(&poll.options),
+            winning_rule: poll.winning_rule,
+            winning_option: poll.winning_option,
             staked_amount: poll.staked_amount,
             total_balance_at_end_poll: poll.total_balance_at_end_poll,
         })
@@ -85,6 +117,28 @@ pub fn query_poll(deps: Deps, poll_id: u64) -> StdResult<PollResponse> {
     }
 }
 
+fn poll_options_response(options: &[PollOptionData]) -> Vec<PollOptionResponse> {
+    options
+        .iter()
+        .map(|option| PollOptionResponse {
+            name: option.name.clone(),
+            execute_msgs: option.execute_data.as_ref().map(|exe_msgs| {
+                exe_msgs
+                    .iter()
+                    .map(|msg| PollExecuteMsg {
+                        order: msg.order,
+                        contract: msg.contract.to_string(),
+                        msg: msg.msg.clone(),
+                        action: msg.action.clone(),
+                        funds: msg.funds.clone(),
+                    })
+                    .collect()
+            }),
+            votes: option.votes,
+        })
+        .collect()
+}
+
 pub fn query_polls(
     deps: Deps,
     filter: Option<PollStatus>,
@@ -104,6 +158,8 @@ pub fn query_polls(
                         order: msg.order,
                         contract: msg.contract.to_string(),
                         msg: msg.msg.clone(),
+                        action: msg.action.clone(),
+                        funds: msg.funds.clone(),
                     })
                     .collect()
             });
@@ -125,6 +181,8 @@ pub fn query_polls(
                 id: poll.id,
                 creator: poll.creator.to_string(),
                 status: poll.status.clone(),
+                category: poll.category.clone(),
+                start_height: poll.start_height,
                 end_time: poll.end_time,
                 title: poll.title.to_string(),
                 description: poll.description.to_string(),
@@ -132,8 +190,16 @@ pub fn query_polls(
                 deposit_amount: poll.deposit_amount,
                 execute_data: execute_messages,
                 migrate_data: migrate_messages,
+                execution_mode: poll.execution_mode.clone(),
+                execution_results: poll.execution_results.clone(),
+                execution_retries: poll.execution_retries,
+                keeper_reward_paid: poll.keeper_reward_paid,
                 yes_votes: poll.yes_votes,
                 no_votes: poll.no_votes,
+                abstain_votes: poll.abstain_votes,
+                options: poll_options_response(&poll.options),
+                winning_rule: poll.winning_rule.clone(),
+                winning_option: poll.winning_option,
                 staked_amount: poll.staked_amount,
                 total_balance_at_end_poll: poll.total_balance_at_end_poll,
             })
@@ -190,29 +256,175 @@ pub fn query_voters(
 
 pub fn query_staker(deps: Deps, env: Env, address: String) -> StdResult<StakerResponse> {
     let address = deps.api.addr_validate(&address)?;
-    let config = load_config(deps.storage)?;
-    let state = load_state(deps.storage)?;
-    let mut token_manager = load_bank(deps.storage, &address)?;
+    let token_manager = load_bank(deps.storage, &address)?;
+    let locked_balance = read_voter_poll_locks(deps.storage, &address, env.block.time.seconds())?;
+
+    let balance = share_to_balance(deps, token_manager.share)?;
+    Ok(StakerResponse {
+        balance,
+        share: token_manager.share,
+        locked_balance,
+    })
+}
+
+pub fn query_delegation(deps: Deps, address: String) -> StdResult<DelegationResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let delegate = may_load_delegation(deps.storage, &address)?;
+    Ok(DelegationResponse {
+        delegate: delegate.map(|addr| addr.to_string()),
+    })
+}
+
+pub fn query_delegators(
+    deps: Deps,
+    delegate: String,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<DelegatorsResponse> {
+    let delegate = deps.api.addr_validate(&delegate)?;
+    let start_after = start_after
+        .map(|addr| deps.api.addr_validate(&addr))
+        .transpose()?;
+    let delegators = read_delegators(deps.storage, &delegate, start_after, limit, order_by)?;
+
+    let delegators_response: StdResult<Vec<DelegatorsResponseItem>> = delegators
+        .into_iter()
+        .map(|delegator| {
+            let share = load_bank(deps.storage, &delegator)?.share;
+            Ok(DelegatorsResponseItem {
+                delegator: delegator.to_string(),
+                share,
+                balance: share_to_balance(deps, share)?,
+            })
+        })
+        .collect();
+
+    Ok(DelegatorsResponse {
+        delegators: delegators_response?,
+    })
+}
+
+pub fn query_delegate(deps: Deps, address: String) -> StdResult<DelegateResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let delegated_share = load_delegated_share(deps.storage, &address)?;
+    let own_share = load_bank(deps.storage, &address)?.share;
+
+    let delegated_balance = share_to_balance(deps, delegated_share)?;
+    let own_balance = share_to_balance(deps, own_share)?;
+    Ok(DelegateResponse {
+        delegated_share,
+        delegated_balance,
+        voting_power: own_balance + delegated_balance,
+    })
+}
+
+pub fn query_unbonding(deps: Deps, address: String) -> StdResult<UnbondingResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(UnbondingResponse {
+        entries: load_unbonding(deps.storage, &address)?,
+    })
+}
+
+pub fn query_vote_nonce(deps: Deps, address: String) -> StdResult<VoteNonceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    Ok(VoteNonceResponse {
+        nonce: load_vote_nonce(deps.storage, &address)?,
+    })
+}
+
+pub fn query_voter_polls(
+    deps: Deps,
+    address: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<VoterPollsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let polls = read_voter_polls(deps.storage, &address, start_after, limit, order_by)?
+        .into_iter()
+        .map(|(poll_id, voter_info)| {
+            let poll = load_poll(deps.storage, poll_id)?;
+            Ok(VoterPollsResponseItem {
+                poll_id,
+                vote: voter_info.vote,
+                balance: voter_info.balance,
+                status: poll.status,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
+
+    Ok(VoterPollsResponse { polls })
+}
+
+pub fn query_voter_stats(deps: Deps, address: String) -> StdResult<VoterStatsResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let stats = load_voter_stats(deps.storage, &address)?;
+    Ok(VoterStatsResponse {
+        polls_voted: stats.polls_voted,
+        total_weight: stats.total_weight,
+    })
+}
 
-    // filter out not in-progress polls
-    token_manager.locked_balance.retain(|(poll_id, _)| {
-        let poll: Poll = load_poll(deps.storage, *poll_id).unwrap();
-        poll.status == PollStatus::InProgress
-    });
+pub fn query_stakers(
+    deps: Deps,
+    start_after: Option<String>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<StakersResponse> {
+    let start_after = start_after
+        .map(|address| deps.api.addr_validate(&address))
+        .transpose()?;
+    let stakers = read_stakers(deps.storage, start_after, limit, order_by)?
+        .into_iter()
+        .map(|(staker, token_manager)| {
+            Ok(StakersResponseItem {
+                staker: staker.to_string(),
+                share: token_manager.share,
+                balance: share_to_balance(deps, token_manager.share)?,
+            })
+        })
+        .collect::<StdResult<Vec<_>>>()?;
 
-    let psi_token = query_token_balance(deps, &config.psi_token, &env.contract.address)?;
-    let total_balance = psi_token.checked_sub(state.total_deposit)?;
+    Ok(StakersResponse { stakers })
+}
 
-    let balance = if !state.total_share.is_zero() {
-        token_manager
-            .share
-            .multiply_ratio(total_balance, state.total_share)
+pub fn query_voting_power(
+    deps: Deps,
+    address: String,
+    poll_id: Option<u64>,
+) -> StdResult<VotingPowerResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let (own_balance, delegated_balance) = if let Some(poll_id) = poll_id {
+        let poll = load_poll(deps.storage, poll_id)?;
+        query_poll_voting_power(deps, &poll, &address)?
     } else {
-        Uint128::zero()
+        let own_share = load_bank(deps.storage, &address)?.share;
+        let delegated_share = load_delegated_share(deps.storage, &address)?;
+        (
+            share_to_balance(deps, own_share)?,
+            share_to_balance(deps, delegated_share)?,
+        )
     };
-    Ok(StakerResponse {
-        balance,
-        share: token_manager.share,
-        locked_balance: token_manager.locked_balance,
+
+    Ok(VotingPowerResponse {
+        own_balance,
+        delegated_balance,
+        voting_power: own_balance + delegated_balance,
+    })
+}
+
+pub fn query_total_voting_power(
+    deps: Deps,
+    at: Option<u64>,
+) -> StdResult<TotalVotingPowerResponse> {
+    let total_share = match at {
+        Some(height) => load_total_share_before(deps.storage, height)?,
+        None => load_state(deps.storage)?.total_share,
+    };
+
+    Ok(TotalVotingPowerResponse {
+        total_share,
+        total_balance: share_to_balance(deps, total_share)?,
     })
 }
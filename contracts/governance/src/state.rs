@@ -2,16 +2,33 @@ use cw_storage_plus::{Bound, Item, Map, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-use cosmwasm_std::{Addr, Binary, Decimal, StdResult, Storage, Uint128};
+use cosmwasm_std::{
+    Addr, Binary, Coin, CosmosMsg, Decimal, Order, StdError, StdResult, Storage, Uint128, WasmMsg,
+};
 use cw0::{calc_range_end, calc_range_start};
 use services::common::OrderBy;
-use services::governance::{PollStatus, VoterInfo};
+use services::governance::{
+    ForfeitedDepositPolicy, PollAction, PollCategory, PollCategoryParams, PollExecutionMode,
+    PollMessageResult, PollParams, PollStatus, UnbondingEntry, VoteOption, VoterInfo,
+    WeightedVoteOption, WinningRule,
+};
 use std::cmp::Ordering;
+use std::convert::TryInto;
 
 static KEY_CONFIG: Item<Config> = Item::new("config");
 static KEY_STATE: Item<State> = Item::new("state");
 static TMP_POLL_ID: Item<u64> = Item::new("tmp_poll_id");
+static TMP_KEEPER: Item<Addr> = Item::new("tmp_keeper");
 static BANK: Map<&Addr, TokenManager> = Map::new("bank");
+static LEGACY_BANK: Map<&Addr, LegacyTokenManager> = Map::new("bank");
+static LEGACY_CONFIG: Item<LegacyConfig> = Item::new("config");
+static LEGACY_STATE: Item<LegacyState> = Item::new("state");
+static LEGACY_POLL: Map<U64Key, LegacyPoll> = Map::new("poll");
+static MIGRATION_PROGRESS: Item<MigrationProgress> = Item::new("migration_progress");
+//key: staker_addr, value: withdrawn tokens waiting for the unbonding period end
+static UNBONDING: Map<&Addr, Vec<UnbondingEntry>> = Map::new("unbonding");
+//key: voter_addr, value: nonce of the next vote signed by the voter
+static VOTE_NONCE: Map<&Addr, u64> = Map::new("vote_nonce");
 
 static POLL: Map<U64Key, Poll> = Map::new("poll");
 //key: poll_status.to_string + poll_id
@@ -19,6 +36,33 @@ static POLL_INDEXER: Map<(String, U64Key), bool> = Map::new("poll_indexer");
 
 //key: poll_id + poll_voter_addr
 static POLL_VOTER: Map<(U64Key, &Addr), VoterInfo> = Map::new("poll_voter");
+//key: voter_addr + poll_id, value: voter info which is kept after the poll end
+static VOTER_POLL: Map<(&Addr, U64Key), VoterInfo> = Map::new("voter_poll");
+//key: voter_addr, value: participation of the voter in polls
+static VOTER_STATS: Map<&Addr, VoterStats> = Map::new("voter_stats");
+//key: voter_addr + poll end_time, value: own tokens locked by votes in polls ending at this time
+static VOTER_LOCKS: Map<(&Addr, U64Key), Vec<(u64, VoterInfo)>> = Map::new("voter_locks");
+//key: voter_addr + poll end_time, value: the largest amount locked till this time.
+//Amounts decrease with time, so the first not expired entry is the locked balance
+static LOCKED_BALANCE: Map<(&Addr, U64Key), Uint128> = Map::new("locked_balance");
+
+//key: delegator_addr, value: delegate_addr
+static DELEGATION: Map<&Addr, Addr> = Map::new("delegation");
+//key: delegate_addr + delegator_addr
+static DELEGATORS: Map<(&Addr, &Addr), bool> = Map::new("delegators");
+//key: delegate_addr, value: sum of delegators shares
+static DELEGATED_SHARE: Map<&Addr, Uint128> = Map::new("delegated_share");
+//key: poll_id + delegate_addr, value: part of delegate vote made with delegated tokens
+static POLL_DELEGATED_VOTE: Map<(U64Key, &Addr), Uint128> = Map::new("poll_delegated_vote");
+//key: poll_id + delegate_addr, value: delegated share of delegators who voted by themselves
+static POLL_WITHHELD_SHARE: Map<(U64Key, &Addr), Uint128> = Map::new("poll_withheld_share");
+//key: delegator_addr + poll_id, value: share withheld from delegate by this delegator
+static DELEGATOR_OVERRIDE: Map<(&Addr, U64Key), Uint128> = Map::new("delegator_override");
+
+//key: staker_addr + block_height, value: staker voting power at the end of the block
+static STAKER_CHECKPOINT: Map<(&Addr, U64Key), StakerCheckpoint> = Map::new("staker_checkpoint");
+//key: block_height, value: total share at the end of the block
+static TOTAL_SHARE_CHECKPOINT: Map<U64Key, Uint128> = Map::new("total_share_checkpoint");
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct Config {
@@ -30,29 +74,125 @@ pub struct Config {
     pub timelock_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub unbonding_period: u64,
+    /// Passed poll can be executed during this period after the timelock
+    pub expiration_period: u64,
+    /// How many times a failed poll can be executed again
+    pub max_execution_retries: u64,
+    /// Paid to the caller of EndPoll, SnapshotPoll and ExecutePoll
+    pub keeper_reward: Uint128,
+    pub max_keeper_reward_per_poll: Uint128,
+    pub forfeited_deposit_policy: ForfeitedDepositPolicy,
+    /// Share of the deposit refunded when quorum is reached but the poll is rejected
+    pub rejected_deposit_refund: Decimal,
+    /// Can veto passed polls before execution
+    pub guardian: Option<Addr>,
+    /// Poll categories which do not use the common parameters
+    pub category_params: Vec<PollCategoryParams>,
+}
+
+impl Config {
+    pub fn poll_params(&self, category: &PollCategory) -> PollParams {
+        self.category_params
+            .iter()
+            .find(|category_params| &category_params.category == category)
+            .map(|category_params| category_params.params.clone())
+            .unwrap_or_else(|| PollParams {
+                quorum: self.quorum,
+                threshold: self.threshold,
+                voting_period: self.voting_period,
+                timelock_period: self.timelock_period,
+                proposal_deposit: self.proposal_deposit,
+            })
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct State {
     pub poll_count: u64,
     pub total_share: Uint128,
+    /// staked tokens with added rewards
+    pub total_staked: Uint128,
     pub total_deposit: Uint128,
+    /// withdrawn tokens which are not claimed yet
+    pub total_unbonding: Uint128,
+    /// tokens for keeper rewards
+    pub keeper_reward_pool: Uint128,
 }
 
-#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
 pub struct TokenManager {
-    pub share: Uint128,                        // total staked balance
-    pub locked_balance: Vec<(u64, VoterInfo)>, // maps poll_id to weight voted
+    pub share: Uint128, // total staked balance
 }
 
-impl Default for TokenManager {
-    fn default() -> Self {
-        let locked_balance: Vec<(u64, VoterInfo)> = vec![];
-        Self {
-            share: Uint128::default(),
-            locked_balance,
-        }
-    }
+// token manager stored before 1.1.0, locks were moved to VOTER_LOCKS
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct LegacyTokenManager {
+    share: Uint128,
+    locked_balance: Vec<(u64, VoterInfo)>,
+}
+
+// config stored before 1.1.0
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct LegacyConfig {
+    owner: Addr,
+    psi_token: Addr,
+    quorum: Decimal,
+    threshold: Decimal,
+    voting_period: u64,
+    timelock_period: u64,
+    proposal_deposit: Uint128,
+    snapshot_period: u64,
+}
+
+// state stored before 1.1.0
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct LegacyState {
+    poll_count: u64,
+    total_share: Uint128,
+    total_deposit: Uint128,
+}
+
+// progress of the migration of entries stored before 1.1.0, removed when it is finished
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct MigrationProgress {
+    height: u64,
+    last_poll_id: Option<u64>,
+    polls_migrated: bool,
+    last_staker: Option<Addr>,
+}
+
+// poll stored before 1.1.0
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct LegacyPoll {
+    id: u64,
+    creator: Addr,
+    status: PollStatus,
+    yes_votes: Uint128,
+    no_votes: Uint128,
+    end_time: u64,
+    title: String,
+    description: String,
+    link: Option<String>,
+    execute_data: Option<Vec<ExecuteData>>,
+    migrate_data: Option<Vec<MigrateData>>,
+    deposit_amount: Uint128,
+    total_balance_at_end_poll: Option<Uint128>,
+    staked_amount: Option<Uint128>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct StakerCheckpoint {
+    pub share: Uint128,
+    pub delegated_share: Uint128,
+    pub delegate: Option<Addr>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema, Default)]
+pub struct VoterStats {
+    pub polls_voted: u64,
+    /// Sum of vote amounts in all polls
+    pub total_weight: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -60,14 +200,26 @@ pub struct Poll {
     pub id: u64,
     pub creator: Addr,
     pub status: PollStatus,
+    pub category: PollCategory,
     pub yes_votes: Uint128,
     pub no_votes: Uint128,
+    pub abstain_votes: Uint128,
+    /// Options of a multi-choice poll, empty for yes/no poll
+    pub options: Vec<PollOptionData>,
+    pub winning_rule: WinningRule,
+    pub winning_option: Option<u32>,
+    /// Voting power is taken from checkpoints made before this block
+    pub start_height: u64,
     pub end_time: u64,
     pub title: String,
     pub description: String,
     pub link: Option<String>,
     pub execute_data: Option<Vec<ExecuteData>>,
     pub migrate_data: Option<Vec<MigrateData>>,
+    pub execution_mode: PollExecutionMode,
+    pub execution_results: Vec<PollMessageResult>,
+    pub execution_retries: u64,
+    pub keeper_reward_paid: Uint128,
     pub deposit_amount: Uint128,
     /// Total balance at the end poll
     pub total_balance_at_end_poll: Option<Uint128>,
@@ -76,7 +228,7 @@ pub struct Poll {
 
 impl Poll {
     pub fn contain_messages(&self) -> bool {
-        let execute_messages_is_empty = if let Some(data) = &self.execute_data {
+        let execute_messages_is_empty = if let Some(data) = self.executable_data() {
             data.is_empty()
         } else {
             true
@@ -89,13 +241,120 @@ impl Poll {
 
         return !execute_messages_is_empty || !migration_messages_is_empty;
     }
+
+    /// Execute messages of the poll, or of the winning option for multi-choice poll
+    pub fn executable_data(&self) -> Option<&Vec<ExecuteData>> {
+        if self.options.is_empty() {
+            self.execute_data.as_ref()
+        } else {
+            self.winning_option
+                .and_then(|index| self.options.get(index as usize))
+                .and_then(|option| option.execute_data.as_ref())
+        }
+    }
+
+    pub fn is_valid_vote(&self, vote: &VoteOption) -> bool {
+        match vote {
+            VoteOption::Abstain => true,
+            VoteOption::Yes | VoteOption::No => self.options.is_empty(),
+            VoteOption::Choice { option } => (*option as usize) < self.options.len(),
+            VoteOption::Split { allocations } => {
+                !allocations.is_empty()
+                    && allocations.iter().enumerate().all(|(i, allocation)| {
+                        !matches!(allocation.option, VoteOption::Split { .. })
+                            && !allocation.weight.is_zero()
+                            && self.is_valid_vote(&allocation.option)
+                            && !allocations[..i]
+                                .iter()
+                                .any(|item| item.option == allocation.option)
+                    })
+            }
+        }
+    }
+
+    pub fn add_votes(&mut self, vote: &VoteOption, amount: Uint128) {
+        match vote {
+            VoteOption::Yes => self.yes_votes += amount,
+            VoteOption::No => self.no_votes += amount,
+            VoteOption::Abstain => self.abstain_votes += amount,
+            VoteOption::Choice { option } => self.options[*option as usize].votes += amount,
+            VoteOption::Split { allocations } => {
+                for (option, option_amount) in split_amount(allocations, amount) {
+                    self.add_votes(option, option_amount);
+                }
+            }
+        }
+    }
+
+    pub fn remove_votes(&mut self, vote: &VoteOption, amount: Uint128) -> StdResult<()> {
+        let votes = match vote {
+            VoteOption::Yes => &mut self.yes_votes,
+            VoteOption::No => &mut self.no_votes,
+            VoteOption::Abstain => &mut self.abstain_votes,
+            VoteOption::Choice { option } => &mut self.options[*option as usize].votes,
+            VoteOption::Split { allocations } => {
+                for (option, option_amount) in split_amount(allocations, amount) {
+                    self.remove_votes(option, option_amount)?;
+                }
+                return Ok(());
+            }
+        };
+        *votes = votes.checked_sub(amount)?;
+        Ok(())
+    }
+}
+
+// splits amount by allocation weights, the last option gets the rounding remainder
+// so the same amount is always split the same way
+fn split_amount(
+    allocations: &[WeightedVoteOption],
+    amount: Uint128,
+) -> Vec<(&VoteOption, Uint128)> {
+    let mut remaining = amount;
+    allocations
+        .iter()
+        .enumerate()
+        .map(|(i, allocation)| {
+            let option_amount = if i + 1 == allocations.len() {
+                remaining
+            } else {
+                std::cmp::min(amount * allocation.weight, remaining)
+            };
+            remaining -= option_amount;
+            (&allocation.option, option_amount)
+        })
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollOptionData {
+    pub name: String,
+    pub execute_data: Option<Vec<ExecuteData>>,
+    pub votes: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct ExecuteData {
     pub order: u64,
     pub contract: Addr,
-    pub msg: Binary,
+    pub msg: Option<Binary>,
+    pub action: Option<PollAction>,
+    #[serde(default)]
+    pub funds: Vec<Coin>,
+}
+
+impl ExecuteData {
+    /// Typed action is converted to the message only at execution
+    pub fn to_cosmos_msg(&self) -> StdResult<CosmosMsg> {
+        match &self.action {
+            Some(action) => action.to_cosmos_msg(self.contract.to_string(), self.funds.clone()),
+            None => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: self.contract.to_string(),
+                msg: self.msg.clone().unwrap_or_default(),
+                funds: self.funds.clone(),
+            })),
+        }
+    }
 }
 impl Eq for ExecuteData {}
 
@@ -169,6 +428,14 @@ pub fn load_tmp_poll_id(storage: &dyn Storage) -> StdResult<u64> {
     TMP_POLL_ID.load(storage)
 }
 
+pub fn store_tmp_keeper(storage: &mut dyn Storage, keeper: &Addr) -> StdResult<()> {
+    TMP_KEEPER.save(storage, keeper)
+}
+
+pub fn load_tmp_keeper(storage: &dyn Storage) -> StdResult<Addr> {
+    TMP_KEEPER.load(storage)
+}
+
 pub fn load_poll(storage: &dyn Storage, poll_id: u64) -> StdResult<Poll> {
     load_poll_internal(storage, poll_id.into())
 }
@@ -201,6 +468,67 @@ pub fn store_bank(
     BANK.save(storage, addr, token_manager)
 }
 
+pub fn read_stakers(
+    storage: &dyn Storage,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<Vec<(Addr, TokenManager)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (start, end, order_by) = match order_by {
+        Some(OrderBy::Asc) => (
+            calc_range_start(start_after).map(Bound::exclusive),
+            None,
+            OrderBy::Asc,
+        ),
+        _ => (
+            None,
+            calc_range_end(start_after).map(Bound::exclusive),
+            OrderBy::Desc,
+        ),
+    };
+
+    // stakers who withdrew everything are left with zero share
+    BANK.range(storage, start, end, order_by.into())
+        .filter(|item| !matches!(item, Ok((_, token_manager)) if token_manager.share.is_zero()))
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            let address_str = std::str::from_utf8(&k)?;
+            Ok((Addr::unchecked(address_str), v))
+        })
+        .collect()
+}
+
+pub fn load_unbonding(storage: &dyn Storage, addr: &Addr) -> StdResult<Vec<UnbondingEntry>> {
+    UNBONDING
+        .may_load(storage, addr)
+        .map(|res| res.unwrap_or_default())
+}
+
+pub fn store_unbonding(
+    storage: &mut dyn Storage,
+    addr: &Addr,
+    entries: &[UnbondingEntry],
+) -> StdResult<()> {
+    if entries.is_empty() {
+        UNBONDING.remove(storage, addr);
+        Ok(())
+    } else {
+        UNBONDING.save(storage, addr, &entries.to_vec())
+    }
+}
+
+pub fn load_vote_nonce(storage: &dyn Storage, addr: &Addr) -> StdResult<u64> {
+    VOTE_NONCE
+        .may_load(storage, addr)
+        .map(|res| res.unwrap_or_default())
+}
+
+pub fn store_vote_nonce(storage: &mut dyn Storage, addr: &Addr, nonce: u64) -> StdResult<()> {
+    VOTE_NONCE.save(storage, addr, &nonce)
+}
+
 pub fn store_poll_indexer(
     storage: &mut dyn Storage,
     status: &PollStatus,
@@ -226,6 +554,471 @@ pub fn remove_poll_voter(storage: &mut dyn Storage, poll_id: u64, voter: &Addr)
     POLL_VOTER.remove(storage, (poll_id.into(), voter))
 }
 
+pub fn store_voter_poll(
+    storage: &mut dyn Storage,
+    voter: &Addr,
+    poll_id: u64,
+    voter_info: &VoterInfo,
+) -> StdResult<()> {
+    let mut stats = load_voter_stats(storage, voter)?;
+    match VOTER_POLL.may_load(storage, (voter, poll_id.into()))? {
+        Some(old_info) => {
+            stats.total_weight = stats.total_weight.checked_sub(old_info.balance)?;
+        }
+        None => stats.polls_voted += 1,
+    }
+    stats.total_weight += voter_info.balance;
+
+    VOTER_STATS.save(storage, voter, &stats)?;
+    VOTER_POLL.save(storage, (voter, poll_id.into()), voter_info)
+}
+
+pub fn remove_voter_poll(storage: &mut dyn Storage, voter: &Addr, poll_id: u64) -> StdResult<()> {
+    if let Some(old_info) = VOTER_POLL.may_load(storage, (voter, poll_id.into()))? {
+        let mut stats = load_voter_stats(storage, voter)?;
+        stats.polls_voted -= 1;
+        stats.total_weight = stats.total_weight.checked_sub(old_info.balance)?;
+        VOTER_STATS.save(storage, voter, &stats)?;
+        VOTER_POLL.remove(storage, (voter, poll_id.into()));
+    }
+    Ok(())
+}
+
+pub fn load_voter_stats(storage: &dyn Storage, voter: &Addr) -> StdResult<VoterStats> {
+    VOTER_STATS
+        .may_load(storage, voter)
+        .map(|res| res.unwrap_or_default())
+}
+
+pub fn read_voter_polls(
+    storage: &dyn Storage,
+    voter: &Addr,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<Vec<(u64, VoterInfo)>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (start, end, order_by) = match order_by {
+        Some(OrderBy::Asc) => (
+            calc_range_start_u64(start_after).map(Bound::exclusive),
+            None,
+            OrderBy::Asc,
+        ),
+        _ => (
+            None,
+            calc_range_end_u64(start_after).map(Bound::exclusive),
+            OrderBy::Desc,
+        ),
+    };
+
+    VOTER_POLL
+        .prefix(voter)
+        .range(storage, start, end, order_by.into())
+        .take(limit)
+        .map(|item| {
+            let (k, v) = item?;
+            Ok((u64_from_key(&k)?, v))
+        })
+        .collect()
+}
+
+/// Locks own tokens of the voter till the poll end
+pub fn store_voter_lock(
+    storage: &mut dyn Storage,
+    voter: &Addr,
+    end_time: u64,
+    poll_id: u64,
+    vote_info: &VoterInfo,
+) -> StdResult<()> {
+    let mut locks = VOTER_LOCKS
+        .may_load(storage, (voter, end_time.into()))?
+        .unwrap_or_default();
+    locks.retain(|(locked_poll_id, _)| *locked_poll_id != poll_id);
+    locks.push((poll_id, vote_info.clone()));
+    VOTER_LOCKS.save(storage, (voter, end_time.into()), &locks)?;
+
+    // the new lock is already covered by a larger one which expires later
+    if let Some((_, locked)) = LOCKED_BALANCE
+        .prefix(voter)
+        .range(
+            storage,
+            Some(Bound::inclusive_int(end_time)),
+            None,
+            Order::Ascending,
+        )
+        .next()
+        .transpose()?
+    {
+        if locked >= vote_info.balance {
+            return Ok(());
+        }
+    }
+
+    // smaller locks which expire earlier are covered by the new one
+    let covered: Vec<u64> = LOCKED_BALANCE
+        .prefix(voter)
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive_int(end_time)),
+            Order::Descending,
+        )
+        .take_while(|item| match item {
+            Ok((_, locked)) => *locked <= vote_info.balance,
+            Err(_) => true,
+        })
+        .map(|item| u64_from_key(&item?.0))
+        .collect::<StdResult<_>>()?;
+    for time in covered {
+        LOCKED_BALANCE.remove(storage, (voter, time.into()));
+    }
+
+    LOCKED_BALANCE.save(storage, (voter, end_time.into()), &vote_info.balance)
+}
+
+/// Unlocks tokens of the voter in the poll, remaining locks of not finished polls are
+/// recounted, so the cost depends only on the number of polls in voting period.
+pub fn remove_voter_lock(
+    storage: &mut dyn Storage,
+    voter: &Addr,
+    end_time: u64,
+    poll_id: u64,
+    current_time: u64,
+) -> StdResult<()> {
+    let mut locks = VOTER_LOCKS
+        .may_load(storage, (voter, end_time.into()))?
+        .unwrap_or_default();
+    locks.retain(|(locked_poll_id, _)| *locked_poll_id != poll_id);
+    if locks.is_empty() {
+        VOTER_LOCKS.remove(storage, (voter, end_time.into()));
+    } else {
+        VOTER_LOCKS.save(storage, (voter, end_time.into()), &locks)?;
+    }
+
+    let not_expired: Vec<u64> = LOCKED_BALANCE
+        .prefix(voter)
+        .range(
+            storage,
+            Some(Bound::exclusive_int(current_time)),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| u64_from_key(&item?.0))
+        .collect::<StdResult<_>>()?;
+    for time in not_expired {
+        LOCKED_BALANCE.remove(storage, (voter, time.into()));
+    }
+
+    let locks = read_locked_amounts(storage, voter, current_time)?;
+    let mut largest = Uint128::zero();
+    for (time, locked) in locks.iter().rev() {
+        if *locked > largest {
+            largest = *locked;
+            LOCKED_BALANCE.save(storage, (voter, (*time).into()), &largest)?;
+        }
+    }
+    Ok(())
+}
+
+// returns end_time and the largest lock of polls ending at this time,
+// only polls which end after current_time are read
+fn read_locked_amounts(
+    storage: &dyn Storage,
+    voter: &Addr,
+    current_time: u64,
+) -> StdResult<Vec<(u64, Uint128)>> {
+    VOTER_LOCKS
+        .prefix(voter)
+        .range(
+            storage,
+            Some(Bound::exclusive_int(current_time)),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| {
+            let (k, locks) = item?;
+            let locked = locks
+                .iter()
+                .map(|(_, vote_info)| vote_info.balance)
+                .max()
+                .unwrap_or_default();
+            Ok((u64_from_key(&k)?, locked))
+        })
+        .collect()
+}
+
+/// Returns votes of the voter in polls which are still in voting period
+pub fn read_voter_poll_locks(
+    storage: &dyn Storage,
+    voter: &Addr,
+    current_time: u64,
+) -> StdResult<Vec<(u64, VoterInfo)>> {
+    let locks: Vec<Vec<(u64, VoterInfo)>> = VOTER_LOCKS
+        .prefix(voter)
+        .range(
+            storage,
+            Some(Bound::exclusive_int(current_time)),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| item.map(|(_, locks)| locks))
+        .collect::<StdResult<_>>()?;
+    Ok(locks.into_iter().flatten().collect())
+}
+
+/// Returns the largest amount of own tokens locked in polls which are still in voting period
+pub fn load_locked_balance(
+    storage: &dyn Storage,
+    voter: &Addr,
+    current_time: u64,
+) -> StdResult<Uint128> {
+    LOCKED_BALANCE
+        .prefix(voter)
+        .range(
+            storage,
+            Some(Bound::exclusive_int(current_time)),
+            None,
+            Order::Ascending,
+        )
+        .next()
+        .map(|item| item.map(|(_, v)| v))
+        .unwrap_or_else(|| Ok(Uint128::zero()))
+}
+
+// expired locks removed per call, so the cost does not depend on the number of voted polls
+const MAX_PRUNED_LOCKS: usize = 10;
+
+/// Removes up to MAX_PRUNED_LOCKS oldest locks of the voter in polls which ended till current_time,
+/// they are not read anymore
+pub fn prune_voter_locks(
+    storage: &mut dyn Storage,
+    voter: &Addr,
+    current_time: u64,
+) -> StdResult<()> {
+    let expired: Vec<u64> = VOTER_LOCKS
+        .prefix(voter)
+        .keys(
+            storage,
+            None,
+            Some(Bound::inclusive_int(current_time)),
+            Order::Ascending,
+        )
+        .take(MAX_PRUNED_LOCKS)
+        .map(|k| u64_from_key(&k))
+        .collect::<StdResult<_>>()?;
+    for time in expired {
+        VOTER_LOCKS.remove(storage, (voter, time.into()));
+    }
+
+    let expired: Vec<u64> = LOCKED_BALANCE
+        .prefix(voter)
+        .keys(
+            storage,
+            None,
+            Some(Bound::inclusive_int(current_time)),
+            Order::Ascending,
+        )
+        .take(MAX_PRUNED_LOCKS)
+        .map(|k| u64_from_key(&k))
+        .collect::<StdResult<_>>()?;
+    for time in expired {
+        LOCKED_BALANCE.remove(storage, (voter, time.into()));
+    }
+    Ok(())
+}
+
+/// Rewrites the config stored before 1.1.0, new parameters keep the old behaviour
+pub fn migrate_config(storage: &mut dyn Storage) -> StdResult<Config> {
+    let legacy = LEGACY_CONFIG.load(storage)?;
+    let config = Config {
+        owner: legacy.owner,
+        psi_token: legacy.psi_token,
+        quorum: legacy.quorum,
+        threshold: legacy.threshold,
+        voting_period: legacy.voting_period,
+        timelock_period: legacy.timelock_period,
+        proposal_deposit: legacy.proposal_deposit,
+        snapshot_period: legacy.snapshot_period,
+        unbonding_period: 0,
+        // passed polls could be executed at any time, give them one more voting period
+        expiration_period: legacy.voting_period,
+        max_execution_retries: 0,
+        keeper_reward: Uint128::zero(),
+        max_keeper_reward_per_poll: Uint128::zero(),
+        forfeited_deposit_policy: ForfeitedDepositPolicy::Stakers,
+        rejected_deposit_refund: Decimal::one(),
+        guardian: None,
+        category_params: vec![],
+    };
+    KEY_CONFIG.save(storage, &config)?;
+    Ok(config)
+}
+
+/// Rewrites the state stored before 1.1.0, all tokens except deposits were staked
+pub fn migrate_state(storage: &mut dyn Storage, psi_balance: Uint128) -> StdResult<()> {
+    let legacy = LEGACY_STATE.load(storage)?;
+    let state = State {
+        poll_count: legacy.poll_count,
+        total_share: legacy.total_share,
+        total_staked: psi_balance.checked_sub(legacy.total_deposit)?,
+        total_deposit: legacy.total_deposit,
+        total_unbonding: Uint128::zero(),
+        keeper_reward_pool: Uint128::zero(),
+    };
+    KEY_STATE.save(storage, &state)
+}
+
+/// Starts the migration of polls and stakers stored before 1.1.0, they are migrated in batches
+/// by `migrate_batch`. Checkpoints are made before `height` so polls see the migrated shares
+pub fn start_migration(storage: &mut dyn Storage, height: u64) -> StdResult<()> {
+    let progress = MigrationProgress {
+        height,
+        last_poll_id: None,
+        polls_migrated: false,
+        last_staker: None,
+    };
+    MIGRATION_PROGRESS.save(storage, &progress)?;
+    let total_share = KEY_STATE.load(storage)?.total_share;
+    store_total_share_checkpoint(storage, height.saturating_sub(1), total_share)
+}
+
+pub fn is_migrating(storage: &dyn Storage) -> StdResult<bool> {
+    Ok(MIGRATION_PROGRESS.may_load(storage)?.is_some())
+}
+
+/// Migrates up to `limit` polls and then stakers stored before 1.1.0.
+/// Returns the number of migrated entries and whether the migration is finished
+pub fn migrate_batch<F>(
+    storage: &mut dyn Storage,
+    limit: usize,
+    category: F,
+) -> StdResult<(usize, bool)>
+where
+    F: Fn(&Option<Vec<ExecuteData>>, &Option<Vec<MigrateData>>) -> PollCategory,
+{
+    let mut progress = MIGRATION_PROGRESS.load(storage)?;
+    let mut migrated = 0;
+
+    if !progress.polls_migrated {
+        let polls: Vec<LegacyPoll> = LEGACY_POLL
+            .range(
+                storage,
+                progress.last_poll_id.map(Bound::exclusive_int),
+                None,
+                Order::Ascending,
+            )
+            .take(limit)
+            .map(|item| item.map(|(_, poll)| poll))
+            .collect::<StdResult<_>>()?;
+        migrated += polls.len();
+        progress.polls_migrated = polls.len() < limit;
+
+        for legacy in polls {
+            progress.last_poll_id = Some(legacy.id);
+            migrate_poll(storage, legacy, progress.height, &category)?;
+        }
+    }
+
+    if progress.polls_migrated {
+        let start = progress
+            .last_staker
+            .as_ref()
+            .map(|staker| Bound::exclusive(staker.as_bytes()));
+        let stakers: Vec<(Addr, LegacyTokenManager)> = LEGACY_BANK
+            .range(storage, start, None, Order::Ascending)
+            .take(limit - migrated)
+            .map(|item| {
+                let (k, token_manager) = item?;
+                Ok((Addr::unchecked(String::from_utf8(k)?), token_manager))
+            })
+            .collect::<StdResult<_>>()?;
+        let finished = stakers.len() < limit - migrated;
+        migrated += stakers.len();
+
+        for (staker, token_manager) in stakers {
+            migrate_staker(storage, &staker, token_manager, progress.height)?;
+            progress.last_staker = Some(staker);
+        }
+
+        if finished {
+            MIGRATION_PROGRESS.remove(storage);
+            return Ok((migrated, true));
+        }
+    }
+
+    MIGRATION_PROGRESS.save(storage, &progress)?;
+    Ok((migrated, false))
+}
+
+// voting power of polls in progress is taken from checkpoints made before start_height
+fn migrate_poll<F>(
+    storage: &mut dyn Storage,
+    legacy: LegacyPoll,
+    start_height: u64,
+    category: &F,
+) -> StdResult<()>
+where
+    F: Fn(&Option<Vec<ExecuteData>>, &Option<Vec<MigrateData>>) -> PollCategory,
+{
+    let poll = Poll {
+        id: legacy.id,
+        creator: legacy.creator,
+        status: legacy.status,
+        category: category(&legacy.execute_data, &legacy.migrate_data),
+        yes_votes: legacy.yes_votes,
+        no_votes: legacy.no_votes,
+        abstain_votes: Uint128::zero(),
+        options: vec![],
+        winning_rule: WinningRule::Plurality,
+        winning_option: None,
+        start_height,
+        end_time: legacy.end_time,
+        title: legacy.title,
+        description: legacy.description,
+        link: legacy.link,
+        execute_data: legacy.execute_data,
+        migrate_data: legacy.migrate_data,
+        execution_mode: PollExecutionMode::AllOrNothing,
+        execution_results: vec![],
+        execution_retries: 0,
+        keeper_reward_paid: Uint128::zero(),
+        deposit_amount: legacy.deposit_amount,
+        total_balance_at_end_poll: legacy.total_balance_at_end_poll,
+        staked_amount: legacy.staked_amount,
+    };
+    POLL.save(storage, legacy.id.into(), &poll)
+}
+
+// locks stored in the token manager are moved to the voter locks, locks of polls
+// which are not in progress are dropped. The locks are also the only votes left by 1.0.0,
+// it removed votes of finished polls on withdraw, so earlier votes are not in the voter history.
+// Stakers had no checkpoints before 1.1.0, the current share is stored before the migration height
+fn migrate_staker(
+    storage: &mut dyn Storage,
+    staker: &Addr,
+    token_manager: LegacyTokenManager,
+    height: u64,
+) -> StdResult<()> {
+    for (poll_id, vote_info) in token_manager.locked_balance {
+        if let Some(poll) = POLL.may_load(storage, poll_id.into())? {
+            if poll.status == PollStatus::InProgress {
+                store_voter_lock(storage, staker, poll.end_time, poll_id, &vote_info)?;
+            }
+            store_voter_poll(storage, staker, poll_id, &vote_info)?;
+        }
+    }
+
+    let token_manager = TokenManager {
+        share: token_manager.share,
+    };
+    BANK.save(storage, staker, &token_manager)?;
+    let checkpoint = StakerCheckpoint {
+        share: token_manager.share,
+        ..StakerCheckpoint::default()
+    };
+    store_staker_checkpoint(storage, staker, height.saturating_sub(1), &checkpoint)
+}
+
 pub fn load_poll_voter(storage: &dyn Storage, poll_id: u64, voter: &Addr) -> StdResult<VoterInfo> {
     POLL_VOTER.load(storage, (poll_id.into(), voter))
 }
@@ -263,6 +1056,191 @@ pub fn read_poll_voters(
         .collect()
 }
 
+pub fn may_load_delegation(storage: &dyn Storage, delegator: &Addr) -> StdResult<Option<Addr>> {
+    DELEGATION.may_load(storage, delegator)
+}
+
+pub fn store_delegation(
+    storage: &mut dyn Storage,
+    delegator: &Addr,
+    delegate: &Addr,
+) -> StdResult<()> {
+    DELEGATION.save(storage, delegator, delegate)?;
+    DELEGATORS.save(storage, (delegate, delegator), &true)
+}
+
+pub fn remove_delegation(storage: &mut dyn Storage, delegator: &Addr, delegate: &Addr) {
+    DELEGATION.remove(storage, delegator);
+    DELEGATORS.remove(storage, (delegate, delegator));
+}
+
+pub fn load_delegated_share(storage: &dyn Storage, delegate: &Addr) -> StdResult<Uint128> {
+    DELEGATED_SHARE
+        .may_load(storage, delegate)
+        .map(|res| res.unwrap_or_default())
+}
+
+pub fn store_delegated_share(
+    storage: &mut dyn Storage,
+    delegate: &Addr,
+    share: Uint128,
+) -> StdResult<()> {
+    if share.is_zero() {
+        DELEGATED_SHARE.remove(storage, delegate);
+        Ok(())
+    } else {
+        DELEGATED_SHARE.save(storage, delegate, &share)
+    }
+}
+
+pub fn load_poll_delegated_vote(
+    storage: &dyn Storage,
+    poll_id: u64,
+    delegate: &Addr,
+) -> StdResult<Uint128> {
+    POLL_DELEGATED_VOTE
+        .may_load(storage, (poll_id.into(), delegate))
+        .map(|res| res.unwrap_or_default())
+}
+
+pub fn store_poll_delegated_vote(
+    storage: &mut dyn Storage,
+    poll_id: u64,
+    delegate: &Addr,
+    amount: Uint128,
+) -> StdResult<()> {
+    if amount.is_zero() {
+        POLL_DELEGATED_VOTE.remove(storage, (poll_id.into(), delegate));
+        Ok(())
+    } else {
+        POLL_DELEGATED_VOTE.save(storage, (poll_id.into(), delegate), &amount)
+    }
+}
+
+pub fn load_poll_withheld_share(
+    storage: &dyn Storage,
+    poll_id: u64,
+    delegate: &Addr,
+) -> StdResult<Uint128> {
+    POLL_WITHHELD_SHARE
+        .may_load(storage, (poll_id.into(), delegate))
+        .map(|res| res.unwrap_or_default())
+}
+
+pub fn store_poll_withheld_share(
+    storage: &mut dyn Storage,
+    poll_id: u64,
+    delegate: &Addr,
+    share: Uint128,
+) -> StdResult<()> {
+    if share.is_zero() {
+        POLL_WITHHELD_SHARE.remove(storage, (poll_id.into(), delegate));
+        Ok(())
+    } else {
+        POLL_WITHHELD_SHARE.save(storage, (poll_id.into(), delegate), &share)
+    }
+}
+
+pub fn may_load_delegator_override(
+    storage: &dyn Storage,
+    delegator: &Addr,
+    poll_id: u64,
+) -> StdResult<Option<Uint128>> {
+    DELEGATOR_OVERRIDE.may_load(storage, (delegator, poll_id.into()))
+}
+
+pub fn store_delegator_override(
+    storage: &mut dyn Storage,
+    delegator: &Addr,
+    poll_id: u64,
+    share: Uint128,
+) -> StdResult<()> {
+    DELEGATOR_OVERRIDE.save(storage, (delegator, poll_id.into()), &share)
+}
+
+pub fn store_staker_checkpoint(
+    storage: &mut dyn Storage,
+    staker: &Addr,
+    height: u64,
+    checkpoint: &StakerCheckpoint,
+) -> StdResult<()> {
+    STAKER_CHECKPOINT.save(storage, (staker, height.into()), checkpoint)
+}
+
+/// returns the last staker checkpoint made before the given block
+pub fn load_staker_checkpoint_before(
+    storage: &dyn Storage,
+    staker: &Addr,
+    height: u64,
+) -> StdResult<StakerCheckpoint> {
+    STAKER_CHECKPOINT
+        .prefix(staker)
+        .range(
+            storage,
+            None,
+            Some(Bound::exclusive_int(height)),
+            Order::Descending,
+        )
+        .next()
+        .map(|item| item.map(|(_, v)| v))
+        .unwrap_or_else(|| Ok(StakerCheckpoint::default()))
+}
+
+pub fn store_total_share_checkpoint(
+    storage: &mut dyn Storage,
+    height: u64,
+    total_share: Uint128,
+) -> StdResult<()> {
+    TOTAL_SHARE_CHECKPOINT.save(storage, height.into(), &total_share)
+}
+
+/// returns the total share at the end of the last block before the given one
+pub fn load_total_share_before(storage: &dyn Storage, height: u64) -> StdResult<Uint128> {
+    TOTAL_SHARE_CHECKPOINT
+        .range(
+            storage,
+            None,
+            Some(Bound::exclusive_int(height)),
+            Order::Descending,
+        )
+        .next()
+        .map(|item| item.map(|(_, v)| v))
+        .unwrap_or_else(|| Ok(Uint128::zero()))
+}
+
+pub fn read_delegators(
+    storage: &dyn Storage,
+    delegate: &Addr,
+    start_after: Option<Addr>,
+    limit: Option<u32>,
+    order_by: Option<OrderBy>,
+) -> StdResult<Vec<Addr>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let (start, end, order_by) = match order_by {
+        Some(OrderBy::Asc) => (
+            calc_range_start(start_after).map(Bound::exclusive),
+            None,
+            OrderBy::Asc,
+        ),
+        _ => (
+            None,
+            calc_range_end(start_after).map(Bound::exclusive),
+            OrderBy::Desc,
+        ),
+    };
+
+    DELEGATORS
+        .prefix(delegate)
+        .range(storage, start, end, order_by.into())
+        .take(limit)
+        .map(|item| {
+            let (k, _) = item?;
+            let address_str = std::str::from_utf8(&k)?;
+            Ok(Addr::unchecked(address_str))
+        })
+        .collect()
+}
+
 const MAX_LIMIT: u32 = 30;
 const DEFAULT_LIMIT: u32 = 10;
 pub fn read_polls(
@@ -320,6 +1298,12 @@ fn calc_range_end_u64(start_after: Option<u64>) -> Option<Vec<u8>> {
     start_after.map(|id| id.to_be_bytes().to_vec())
 }
 
+fn u64_from_key(key: &[u8]) -> StdResult<u64> {
+    key.try_into()
+        .map(u64::from_be_bytes)
+        .map_err(|_| StdError::generic_err("Invalid u64 key"))
+}
+
 #[cfg(test)]
 mod test {
     use crate::state::VoterInfo;
@@ -339,14 +1323,24 @@ mod test {
                 id: 0u64,
                 creator: Addr::unchecked(""),
                 status: PollStatus::Failed,
+                category: PollCategory::Text,
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                abstain_votes: Uint128::zero(),
+                options: vec![],
+                winning_rule: WinningRule::Plurality,
+                winning_option: None,
+                start_height: 0u64,
                 end_time: 0u64,
                 title: String::default(),
                 description: String::default(),
                 link: None,
                 execute_data: None,
                 migrate_data: None,
+                execution_mode: PollExecutionMode::AllOrNothing,
+                execution_results: vec![],
+                execution_retries: 0,
+                keeper_reward_paid: Uint128::zero(),
                 deposit_amount: Uint128::zero(),
                 total_balance_at_end_poll: None,
                 staked_amount: None,
@@ -764,10 +1758,14 @@ mod test {
         let exec_msg = ExecuteData {
             order: 1u64,
             contract: Addr::unchecked("som_contract"),
-            msg: to_binary(&Cw20ExecuteMsg::Burn {
-                amount: Uint128::new(30),
-            })
-            .unwrap(),
+            msg: Some(
+                to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::new(30),
+                })
+                .unwrap(),
+            ),
+            action: None,
+            funds: vec![],
         };
 
         let migrate_msg = MigrateData {
@@ -792,4 +1790,223 @@ mod test {
         poll.migrate_data = Some(vec![migrate_msg]);
         assert_eq!(poll.contain_messages(), true);
     }
+
+    #[test]
+    fn load_checkpoints_before_height() {
+        let mut deps = mock_dependencies(&[]);
+        let staker = addr_from_i(1);
+        for height in [10u64, 20u64] {
+            let checkpoint = StakerCheckpoint {
+                share: Uint128::from(height),
+                delegated_share: Uint128::from(height * 2),
+                delegate: Some(addr_from_i(2)),
+            };
+            store_staker_checkpoint(&mut deps.storage, &staker, height, &checkpoint).unwrap();
+            store_total_share_checkpoint(&mut deps.storage, height, Uint128::from(height * 3))
+                .unwrap();
+        }
+        // checkpoint of another staker must not be taken into account
+        store_staker_checkpoint(
+            &mut deps.storage,
+            &addr_from_i(0),
+            15,
+            &StakerCheckpoint::default(),
+        )
+        .unwrap();
+
+        for (height, expected) in [(10u64, 0u128), (11, 10), (20, 10), (21, 20), (1000, 20)] {
+            let checkpoint = load_staker_checkpoint_before(&deps.storage, &staker, height).unwrap();
+            assert_eq!(checkpoint.share, Uint128::new(expected));
+            assert_eq!(checkpoint.delegated_share, Uint128::new(expected * 2));
+            assert_eq!(
+                load_total_share_before(&deps.storage, height).unwrap(),
+                Uint128::new(expected * 3)
+            );
+        }
+        assert_eq!(
+            load_staker_checkpoint_before(&deps.storage, &staker, 10).unwrap(),
+            StakerCheckpoint::default()
+        );
+    }
+
+    fn yes_vote(balance: u128) -> VoterInfo {
+        VoterInfo {
+            vote: VoteOption::Yes,
+            balance: Uint128::from(balance),
+        }
+    }
+
+    #[test]
+    fn locked_balance_expires_with_polls() {
+        let mut deps = mock_dependencies(&[]);
+        let voter = addr_from_i(1);
+        // poll_id, end_time, locked amount
+        for (poll_id, end_time, amount) in [(1u64, 100u64, 10u128), (2, 300, 30), (3, 200, 20)] {
+            store_voter_lock(
+                &mut deps.storage,
+                &voter,
+                end_time,
+                poll_id,
+                &yes_vote(amount),
+            )
+            .unwrap();
+        }
+        store_voter_lock(&mut deps.storage, &addr_from_i(2), 400, 4, &yes_vote(40)).unwrap();
+
+        for (time, expected) in [(0u64, 30u128), (200, 30), (299, 30), (300, 0)] {
+            assert_eq!(
+                load_locked_balance(&deps.storage, &voter, time).unwrap(),
+                Uint128::new(expected)
+            );
+        }
+
+        // removed lock is not counted anymore, other locks are kept
+        remove_voter_lock(&mut deps.storage, &voter, 300, 2, 50).unwrap();
+        for (time, expected) in [(50u64, 20u128), (100, 20), (200, 0)] {
+            assert_eq!(
+                load_locked_balance(&deps.storage, &voter, time).unwrap(),
+                Uint128::new(expected)
+            );
+        }
+        assert_eq!(
+            read_voter_poll_locks(&deps.storage, &voter, 50).unwrap(),
+            vec![(1, yes_vote(10)), (3, yes_vote(20))]
+        );
+    }
+
+    #[test]
+    fn expired_voter_locks_are_pruned() {
+        let mut deps = mock_dependencies(&[]);
+        let voter = addr_from_i(1);
+        for (poll_id, end_time, amount) in [(1u64, 100u64, 30u128), (2, 200, 20), (3, 300, 10)] {
+            store_voter_lock(
+                &mut deps.storage,
+                &voter,
+                end_time,
+                poll_id,
+                &yes_vote(amount),
+            )
+            .unwrap();
+        }
+        store_voter_lock(&mut deps.storage, &addr_from_i(2), 100, 1, &yes_vote(40)).unwrap();
+
+        prune_voter_locks(&mut deps.storage, &voter, 200).unwrap();
+        assert_eq!(
+            read_voter_poll_locks(&deps.storage, &voter, 0).unwrap(),
+            vec![(3, yes_vote(10))]
+        );
+        assert_eq!(
+            LOCKED_BALANCE
+                .prefix(&voter)
+                .range(&deps.storage, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap(),
+            vec![(300u64.to_be_bytes().to_vec(), Uint128::new(10))]
+        );
+        assert_eq!(
+            load_locked_balance(&deps.storage, &voter, 200).unwrap(),
+            Uint128::new(10)
+        );
+        // locks of other voters are kept
+        assert_eq!(
+            read_voter_poll_locks(&deps.storage, &addr_from_i(2), 0).unwrap(),
+            vec![(1, yes_vote(40))]
+        );
+    }
+
+    #[test]
+    fn migrate_polls_and_stakers_in_batches() {
+        let mut deps = mock_dependencies(&[]);
+        for (poll_id, status) in [(1u64, PollStatus::InProgress), (2, PollStatus::Passed)] {
+            let poll = LegacyPoll {
+                id: poll_id,
+                creator: addr_from_i(0),
+                status,
+                yes_votes: Uint128::zero(),
+                no_votes: Uint128::zero(),
+                end_time: 100,
+                title: String::default(),
+                description: String::default(),
+                link: None,
+                execute_data: None,
+                migrate_data: None,
+                deposit_amount: Uint128::zero(),
+                total_balance_at_end_poll: None,
+                staked_amount: None,
+            };
+            LEGACY_POLL
+                .save(&mut deps.storage, poll_id.into(), &poll)
+                .unwrap();
+        }
+        for i in 1..=3 {
+            let token_manager = LegacyTokenManager {
+                share: Uint128::new(i as u128 * 10),
+                locked_balance: vec![(1, yes_vote(i as u128)), (2, yes_vote(20))],
+            };
+            LEGACY_BANK
+                .save(&mut deps.storage, &addr_from_i(i), &token_manager)
+                .unwrap();
+        }
+        let state = State {
+            poll_count: 2,
+            total_share: Uint128::new(60),
+            total_staked: Uint128::new(60),
+            total_deposit: Uint128::zero(),
+            total_unbonding: Uint128::zero(),
+            keeper_reward_pool: Uint128::zero(),
+        };
+        store_state(&mut deps.storage, &state).unwrap();
+
+        start_migration(&mut deps.storage, 50).unwrap();
+        assert!(is_migrating(&deps.storage).unwrap());
+        for expected in [(2, false), (2, false), (1, true)] {
+            let migrated = migrate_batch(&mut deps.storage, 2, |_, _| PollCategory::Text).unwrap();
+            assert_eq!(migrated, expected);
+        }
+        assert!(!is_migrating(&deps.storage).unwrap());
+
+        let poll = load_poll(&deps.storage, 2).unwrap();
+        assert_eq!(poll.status, PollStatus::Passed);
+        assert_eq!(poll.start_height, 50);
+        assert_eq!(
+            load_total_share_before(&deps.storage, 50).unwrap(),
+            Uint128::new(60)
+        );
+        for i in 1..=3 {
+            let voter = addr_from_i(i);
+            assert_eq!(
+                load_bank(&deps.storage, &voter).unwrap(),
+                TokenManager {
+                    share: Uint128::new(i as u128 * 10)
+                }
+            );
+            assert_eq!(
+                load_staker_checkpoint_before(&deps.storage, &voter, 50)
+                    .unwrap()
+                    .share,
+                Uint128::new(i as u128 * 10)
+            );
+            // locks of polls which are not in progress are dropped
+            assert_eq!(
+                read_voter_poll_locks(&deps.storage, &voter, 0).unwrap(),
+                vec![(1, yes_vote(i as u128))]
+            );
+            // but votes are kept in the voter history
+            assert_eq!(
+                read_voter_polls(&deps.storage, &voter, None, None, Some(OrderBy::Asc)).unwrap(),
+                vec![(1, yes_vote(i as u128)), (2, yes_vote(20))]
+            );
+            assert_eq!(
+                load_voter_stats(&deps.storage, &voter).unwrap(),
+                VoterStats {
+                    polls_voted: 2,
+                    total_weight: Uint128::new(i as u128 + 20),
+                }
+            );
+            assert_eq!(
+                load_locked_balance(&deps.storage, &voter, 0).unwrap(),
+                Uint128::new(i as u128)
+            );
+        }
+    }
 }
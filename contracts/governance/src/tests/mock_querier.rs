@@ -1,9 +1,10 @@
 use cosmwasm_std::testing::{MockApi, MockQuerier, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    from_slice, to_binary, Addr, Coin, ContractResult, Empty, OwnedDeps, Querier, QuerierResult,
-    QueryRequest, SystemError, SystemResult, Uint128, WasmQuery,
+    from_binary, from_slice, to_binary, Addr, Api, CanonicalAddr, Coin, ContractResult, Empty,
+    OwnedDeps, Querier, QuerierResult, QueryRequest, RecoverPubkeyError, StdError, StdResult,
+    SystemError, SystemResult, Uint128, VerificationError, WasmQuery,
 };
-use cosmwasm_storage::to_length_prefixed;
+use cw20::{BalanceResponse, Cw20QueryMsg};
 use std::collections::HashMap;
 
 /// copypasted from TerraSwap
@@ -11,17 +12,102 @@ use std::collections::HashMap;
 /// this uses our CustomQuerier.
 pub fn mock_dependencies(
     contract_balance: &[Coin],
-) -> OwnedDeps<MockStorage, MockApi, WasmMockQuerier> {
+) -> OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier> {
     let custom_querier: WasmMockQuerier =
         WasmMockQuerier::new(MockQuerier::new(&[(MOCK_CONTRACT_ADDR, contract_balance)]));
 
     OwnedDeps {
         storage: MockStorage::default(),
-        api: MockApi::default(),
+        api: WasmMockApi::default(),
         querier: custom_querier,
     }
 }
 
+/// Prefix of addresses humanized from 20 byte canonical addresses, e.g. public key hashes
+pub const ACCOUNT_PREFIX: &str = "account";
+const ACCOUNT_LENGTH: usize = 20;
+
+/// MockApi which also supports 20 byte canonical addresses of accounts
+/// as `ACCOUNT_PREFIX` followed by hex encoded bytes
+#[derive(Copy, Clone, Default)]
+pub struct WasmMockApi {
+    base: MockApi,
+}
+
+impl Api for WasmMockApi {
+    fn addr_validate(&self, human: &str) -> StdResult<Addr> {
+        self.addr_canonicalize(human)?;
+        Ok(Addr::unchecked(human))
+    }
+
+    fn addr_canonicalize(&self, human: &str) -> StdResult<CanonicalAddr> {
+        match human.strip_prefix(ACCOUNT_PREFIX) {
+            Some(hex) if hex.len() == ACCOUNT_LENGTH * 2 => (0..hex.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&hex[i..i + 2], 16))
+                .collect::<Result<Vec<u8>, _>>()
+                .map(CanonicalAddr::from)
+                .map_err(|_| StdError::generic_err("Invalid input: account address not hex")),
+            _ => self.base.addr_canonicalize(human),
+        }
+    }
+
+    fn addr_humanize(&self, canonical: &CanonicalAddr) -> StdResult<Addr> {
+        if canonical.len() != ACCOUNT_LENGTH {
+            return self.base.addr_humanize(canonical);
+        }
+        let hex: String = canonical
+            .as_slice()
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect();
+        Ok(Addr::unchecked(format!("{}{}", ACCOUNT_PREFIX, hex)))
+    }
+
+    fn secp256k1_verify(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.base
+            .secp256k1_verify(message_hash, signature, public_key)
+    }
+
+    fn secp256k1_recover_pubkey(
+        &self,
+        message_hash: &[u8],
+        signature: &[u8],
+        recovery_param: u8,
+    ) -> Result<Vec<u8>, RecoverPubkeyError> {
+        self.base
+            .secp256k1_recover_pubkey(message_hash, signature, recovery_param)
+    }
+
+    fn ed25519_verify(
+        &self,
+        message: &[u8],
+        signature: &[u8],
+        public_key: &[u8],
+    ) -> Result<bool, VerificationError> {
+        self.base.ed25519_verify(message, signature, public_key)
+    }
+
+    fn ed25519_batch_verify(
+        &self,
+        messages: &[&[u8]],
+        signatures: &[&[u8]],
+        public_keys: &[&[u8]],
+    ) -> Result<bool, VerificationError> {
+        self.base
+            .ed25519_batch_verify(messages, signatures, public_keys)
+    }
+
+    fn debug(&self, message: &str) {
+        self.base.debug(message)
+    }
+}
+
 pub struct WasmMockQuerier {
     base: MockQuerier<Empty>,
     token_querier: TokenQuerier,
@@ -74,44 +160,38 @@ impl Querier for WasmMockQuerier {
 impl WasmMockQuerier {
     pub fn handle_query(&self, request: &QueryRequest<Empty>) -> QuerierResult {
         match &request {
-            QueryRequest::Wasm(WasmQuery::Raw { contract_addr, key }) => {
-                let key: &[u8] = key.as_slice();
-
-                let balances: &HashMap<String, Uint128> =
-                    match self.token_querier.balances.get(contract_addr) {
-                        Some(balances) => balances,
-                        None => {
-                            return SystemResult::Err(SystemError::InvalidRequest {
-                                error: format!(
-                                    "No balance info exists for the contract {}",
-                                    contract_addr
-                                ),
-                                request: key.into(),
-                            })
-                        }
-                    };
-
-                let prefix_balance = to_length_prefixed(b"balance").to_vec();
-
-                if key[..prefix_balance.len()].to_vec() == prefix_balance {
-                    let key_address: &[u8] = &key[prefix_balance.len()..];
-                    let address: Addr = Addr::unchecked(std::str::from_utf8(key_address).unwrap());
-
-                    let balance = match balances.get(&address.to_string()) {
-                        Some(v) => v,
+            QueryRequest::Wasm(WasmQuery::Smart { contract_addr, msg }) => match from_binary(msg) {
+                Ok(Cw20QueryMsg::Balance { address }) => {
+                    let balances: &HashMap<String, Uint128> =
+                        match self.token_querier.balances.get(contract_addr) {
+                            Some(balances) => balances,
+                            None => {
+                                return SystemResult::Err(SystemError::InvalidRequest {
+                                    error: format!(
+                                        "No balance info exists for the contract {}",
+                                        contract_addr
+                                    ),
+                                    request: msg.as_slice().into(),
+                                })
+                            }
+                        };
+
+                    let balance = match balances.get(&address) {
+                        Some(v) => *v,
                         None => {
                             return SystemResult::Err(SystemError::InvalidRequest {
                                 error: "Balance not found".to_string(),
-                                request: key.into(),
+                                request: msg.as_slice().into(),
                             })
                         }
                     };
 
-                    SystemResult::Ok(ContractResult::from(to_binary(&balance)))
-                } else {
-                    panic!("DO NOT ENTER HERE")
+                    SystemResult::Ok(ContractResult::from(to_binary(&BalanceResponse {
+                        balance,
+                    })))
                 }
-            }
+                _ => panic!("DO NOT ENTER HERE"),
+            },
             _ => self.base.handle_query(request),
         }
     }
@@ -1,26 +1,41 @@
-use crate::contract::{execute, instantiate, query, reply, POLL_EXECUTE_REPLY_ID};
+use crate::contract::{
+    execute, instantiate, migrate, query, reply, CONTRACT_NAME, CONTRACT_VERSION,
+    POLL_EXECUTE_REPLY_ID,
+};
+use crate::error::ContractError;
 use crate::state::{
-    load_bank, load_config, load_poll_voter, load_state, load_tmp_poll_id, remove_poll_indexer,
-    store_bank, store_poll, store_poll_indexer, store_poll_voter, Config, Poll, State,
-    TokenManager,
+    load_config, load_poll_voter, load_state, load_tmp_poll_id, read_voter_poll_locks,
+    remove_poll_indexer, store_config, store_poll_indexer, Config, State,
 };
-use crate::tests::mock_querier::{mock_dependencies, WasmMockQuerier};
+use crate::tests::mock_querier::{mock_dependencies, WasmMockApi, WasmMockQuerier, ACCOUNT_PREFIX};
 
 use crate::querier::query_token_balance;
-use cosmwasm_std::testing::{mock_env, mock_info, MockApi, MockStorage, MOCK_CONTRACT_ADDR};
+use cosmwasm_std::testing::{mock_env, mock_info, MockStorage, MOCK_CONTRACT_ADDR};
 use cosmwasm_std::{
-    attr, coins, from_binary, to_binary, Addr, ContractResult, CosmosMsg, Decimal, Env, OwnedDeps,
-    Reply, Response, StdError, SubMsg, Timestamp, Uint128, WasmMsg,
+    attr, coin, coins, from_binary, to_binary, Addr, BankMsg, Binary, Coin, ContractResult,
+    CosmosMsg, Decimal, DepsMut, Env, OwnedDeps, Reply, Response, StdError, StdResult, Storage,
+    SubMsg, SubMsgExecutionResponse, Timestamp, Uint128, WasmMsg,
 };
 use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::{Map, U64Key};
+use k256::ecdsa::{signature::DigestSigner, Signature, SigningKey, VerifyingKey};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use services::common::OrderBy;
 use services::governance::{
-    AnyoneMsg, ConfigResponse, Cw20HookMsg, ExecuteMsg, GovernanceMsg, InstantiateMsg,
-    PollExecuteMsg, PollMigrateMsg, PollResponse, PollStatus, PollsResponse, QueryMsg,
-    StakerResponse, VoteOption, VoterInfo, VotersResponse, VotersResponseItem, YourselfMsg,
+    AnyoneMsg, ConfigResponse, Cw20HookMsg, DelegateResponse, DelegationResponse,
+    DelegatorsResponse, DelegatorsResponseItem, ExecuteMsg, ForfeitedDepositPolicy, GovernanceMsg,
+    GuardianMsg, InstantiateMsg, PollAction, PollCategory, PollCategoryParams, PollExecuteMsg,
+    PollExecutionMode, PollMessageKind, PollMessageResult, PollMessageStatus, PollMigrateMsg,
+    PollOption, PollParams, PollResponse, PollStatus, PollsResponse, QueryMsg, SignedVote,
+    StakerResponse, StakersResponse, StakersResponseItem, StateResponse, TotalVotingPowerResponse,
+    UnbondingEntry, UnbondingResponse, VoteNonceResponse, VoteOption, VoterInfo,
+    VoterPollsResponse, VoterPollsResponseItem, VoterStatsResponse, VotersResponse,
+    VotersResponseItem, VotingPowerResponse, WeightedVoteOption, WinningRule, YourselfMsg,
 };
+use services::migration::{init_contract_version, migrate_contract, MigrationFn};
+use services::ownership::{OwnershipMsg, PendingOwner};
+use sha2::{Digest, Sha256};
 
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 pub struct MigrateMsg {
@@ -32,14 +47,17 @@ const TEST_CREATOR: &str = "creator";
 const TEST_VOTER: &str = "voter1";
 const TEST_VOTER_2: &str = "voter2";
 const TEST_VOTER_3: &str = "voter3";
+const TEST_GUARDIAN: &str = "guardian";
 const DEFAULT_QUORUM: u64 = 30u64;
 const DEFAULT_THRESHOLD: u64 = 50u64;
 const DEFAULT_VOTING_PERIOD: u64 = 20000u64;
 const DEFAULT_FIX_PERIOD: u64 = 10u64;
+const DEFAULT_UNBONDING_PERIOD: u64 = 0u64;
+const DEFAULT_EXPIRATION_PERIOD: u64 = 20000u64;
 const DEFAULT_TIMELOCK_PERIOD: u64 = 10000u64;
 const DEFAULT_PROPOSAL_DEPOSIT: u128 = 10000000000u128;
 
-fn mock_init(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) {
+fn mock_init(deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>) {
     let msg = InstantiateMsg {
         quorum: Decimal::percent(DEFAULT_QUORUM),
         threshold: Decimal::percent(DEFAULT_THRESHOLD),
@@ -47,6 +65,9 @@ fn mock_init(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) {
         timelock_period: DEFAULT_TIMELOCK_PERIOD,
         proposal_deposit: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        unbonding_period: DEFAULT_UNBONDING_PERIOD,
+        expiration_period: DEFAULT_EXPIRATION_PERIOD,
+        category_params: vec![],
     };
 
     let env = mock_env();
@@ -66,6 +87,15 @@ fn mock_init(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) {
             timelock_period: DEFAULT_TIMELOCK_PERIOD,
             proposal_deposit: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
             snapshot_period: DEFAULT_FIX_PERIOD,
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
+            expiration_period: DEFAULT_EXPIRATION_PERIOD,
+            max_execution_retries: 0,
+            keeper_reward: Uint128::zero(),
+            max_keeper_reward_per_poll: Uint128::zero(),
+            forfeited_deposit_policy: ForfeitedDepositPolicy::Stakers,
+            rejected_deposit_refund: Decimal::one(),
+            guardian: None,
+            category_params: vec![],
         }
     );
 
@@ -89,6 +119,15 @@ fn mock_init(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) {
             timelock_period: DEFAULT_TIMELOCK_PERIOD,
             proposal_deposit: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
             snapshot_period: DEFAULT_FIX_PERIOD,
+            unbonding_period: DEFAULT_UNBONDING_PERIOD,
+            expiration_period: DEFAULT_EXPIRATION_PERIOD,
+            max_execution_retries: 0,
+            keeper_reward: Uint128::zero(),
+            max_keeper_reward_per_poll: Uint128::zero(),
+            forfeited_deposit_policy: ForfeitedDepositPolicy::Stakers,
+            rejected_deposit_refund: Decimal::one(),
+            guardian: None,
+            category_params: vec![],
         }
     );
 
@@ -100,11 +139,17 @@ fn mock_init(deps: &mut OwnedDeps<MockStorage, MockApi, WasmMockQuerier>) {
 
     // can't change token_address
     let res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone());
-    if let StdError::GenericErr { msg } = res.err().unwrap() {
-        assert_eq!("unauthorized", msg);
-    } else {
-        panic!("wrong error");
-    }
+    assert_eq!(res.err().unwrap(), ContractError::Unauthorized);
+}
+
+// tokens in tests are staked with `mock_env()`, polls are created in the next block
+// so those tokens are counted in the voting power checkpoints
+const POLL_START_HEIGHT: u64 = 12_346;
+
+fn mock_env_poll_start() -> Env {
+    let mut env = mock_env();
+    env.block.height = POLL_START_HEIGHT;
+    env
 }
 
 fn mock_env_height(height: u64, time: u64) -> Env {
@@ -125,7 +170,10 @@ fn proper_initialization() {
         State {
             poll_count: 0,
             total_share: Uint128::zero(),
+            total_staked: Uint128::zero(),
             total_deposit: Uint128::zero(),
+            total_unbonding: Uint128::zero(),
+            keeper_reward_pool: Uint128::zero(),
         }
     );
 }
@@ -156,12 +204,15 @@ fn fails_init_invalid_quorum() {
         timelock_period: DEFAULT_TIMELOCK_PERIOD,
         proposal_deposit: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        unbonding_period: DEFAULT_UNBONDING_PERIOD,
+        expiration_period: DEFAULT_EXPIRATION_PERIOD,
+        category_params: vec![],
     };
 
     let res = instantiate(deps.as_mut(), env, info, msg);
     match res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "quorum must be 0 to 1"),
+        Err(ContractError::InvalidQuorum) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -178,12 +229,15 @@ fn fails_init_invalid_threshold() {
         timelock_period: DEFAULT_TIMELOCK_PERIOD,
         proposal_deposit: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
         snapshot_period: DEFAULT_FIX_PERIOD,
+        unbonding_period: DEFAULT_UNBONDING_PERIOD,
+        expiration_period: DEFAULT_EXPIRATION_PERIOD,
+        category_params: vec![],
     };
 
     let res = instantiate(deps.as_mut(), env, info, msg);
     match res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "threshold must be 0 to 1"),
+        Err(ContractError::InvalidThreshold) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -199,7 +253,7 @@ fn fails_create_poll_invalid_title() {
     let res = execute(deps.as_mut(), env.clone(), info.clone(), msg);
     match res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Title too short"),
+        Err(ContractError::TitleTooShort) => {}
         Err(_) => panic!("Unknown error"),
     }
 
@@ -213,7 +267,7 @@ fn fails_create_poll_invalid_title() {
 
     match execute(deps.as_mut(), env, info, msg) {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Title too long"),
+        Err(ContractError::TitleTooLong) => {}
         Err(_) => panic!("Unknown error"),
     }
 }
@@ -228,7 +282,7 @@ fn fails_create_poll_invalid_description() {
     let info = mock_info(VOTING_TOKEN, &vec![]);
     match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Description too short"),
+        Err(ContractError::DescriptionTooShort) => {}
         Err(_) => panic!("Unknown error"),
     }
 
@@ -242,7 +296,7 @@ fn fails_create_poll_invalid_description() {
 
     match execute(deps.as_mut(), env, info, msg) {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Description too long"),
+        Err(ContractError::DescriptionTooLong) => {}
         Err(_) => panic!("Unknown error"),
     }
 }
@@ -257,7 +311,7 @@ fn fails_create_poll_invalid_link() {
     let info = mock_info(VOTING_TOKEN, &vec![]);
     match execute(deps.as_mut(), env.clone(), info.clone(), msg) {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Link too short"),
+        Err(ContractError::LinkTooShort) => {}
         Err(_) => panic!("Unknown error"),
     }
 
@@ -271,7 +325,7 @@ fn fails_create_poll_invalid_link() {
 
     match execute(deps.as_mut(), env, info, msg) {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Link too long"),
+        Err(ContractError::LinkTooLong) => {}
         Err(_) => panic!("Unknown error"),
     }
 }
@@ -290,6 +344,10 @@ fn fails_create_poll_invalid_deposit() {
             link: None,
             execute_msgs: None,
             migrate_msgs: None,
+            execution_mode: None,
+            category: None,
+            options: None,
+            winning_rule: None,
         })
         .unwrap(),
     });
@@ -297,10 +355,9 @@ fn fails_create_poll_invalid_deposit() {
     let info = mock_info(VOTING_TOKEN, &vec![]);
     match execute(deps.as_mut(), env, info, msg) {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
-            msg,
-            format!("Must deposit more than {} token", DEFAULT_PROPOSAL_DEPOSIT)
-        ),
+        Err(ContractError::InsufficientDeposit(amount)) => {
+            assert_eq!(amount, Uint128::new(DEFAULT_PROPOSAL_DEPOSIT))
+        }
         Err(_) => panic!("Unknown error"),
     }
 }
@@ -321,6 +378,10 @@ fn create_poll_msg<A: Into<String>>(
             link: link.map(|l| l.into()),
             execute_msgs: execute_msg,
             migrate_msgs: migrate_msg,
+            execution_mode: None,
+            category: None,
+            options: None,
+            winning_rule: None,
         })
         .unwrap(),
     });
@@ -331,7 +392,7 @@ fn create_poll_msg<A: Into<String>>(
 fn happy_days_create_poll() {
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
-    let env = mock_env_height(0, 10000);
+    let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(VOTING_TOKEN, &vec![]);
 
     let msg = create_poll_msg("test", "test", None, None, None);
@@ -350,7 +411,7 @@ fn happy_days_create_poll() {
 fn query_polls() {
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
-    let env = mock_env_height(0, 0);
+    let env = mock_env_height(POLL_START_HEIGHT, 0);
     let info = mock_info(VOTING_TOKEN, &vec![]);
 
     let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
@@ -373,19 +434,25 @@ fn query_polls() {
     execute_msgs.push(PollExecuteMsg {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz.clone(),
+        msg: Some(exec_msg_bz.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 3u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz3.clone(),
+        msg: Some(exec_msg_bz3.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 2u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz2.clone(),
+        msg: Some(exec_msg_bz2.clone()),
+        action: None,
+        funds: vec![],
     });
 
     let msg = create_poll_msg(
@@ -419,6 +486,8 @@ fn query_polls() {
                 id: 1u64,
                 creator: TEST_CREATOR.to_string(),
                 status: PollStatus::InProgress,
+                category: PollCategory::TreasurySpend,
+                start_height: POLL_START_HEIGHT,
                 end_time: DEFAULT_VOTING_PERIOD,
                 title: "test".to_string(),
                 description: "test".to_string(),
@@ -426,8 +495,16 @@ fn query_polls() {
                 deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
                 execute_data: Some(execute_msgs.clone()),
                 migrate_data: None,
+                execution_mode: PollExecutionMode::AllOrNothing,
+                execution_results: vec![],
+                execution_retries: 0,
+                keeper_reward_paid: Uint128::zero(),
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                abstain_votes: Uint128::zero(),
+                options: vec![],
+                winning_rule: WinningRule::Plurality,
+                winning_option: None,
                 staked_amount: None,
                 total_balance_at_end_poll: None,
             },
@@ -435,6 +512,8 @@ fn query_polls() {
                 id: 2u64,
                 creator: TEST_CREATOR.to_string(),
                 status: PollStatus::InProgress,
+                category: PollCategory::Text,
+                start_height: POLL_START_HEIGHT,
                 end_time: DEFAULT_VOTING_PERIOD,
                 title: "test2".to_string(),
                 description: "test2".to_string(),
@@ -442,8 +521,16 @@ fn query_polls() {
                 deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
                 execute_data: None,
                 migrate_data: None,
+                execution_mode: PollExecutionMode::AllOrNothing,
+                execution_results: vec![],
+                execution_retries: 0,
+                keeper_reward_paid: Uint128::zero(),
                 yes_votes: Uint128::zero(),
                 no_votes: Uint128::zero(),
+                abstain_votes: Uint128::zero(),
+                options: vec![],
+                winning_rule: WinningRule::Plurality,
+                winning_option: None,
                 staked_amount: None,
                 total_balance_at_end_poll: None,
             },
@@ -468,6 +555,8 @@ fn query_polls() {
             id: 2u64,
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
+            category: PollCategory::Text,
+            start_height: POLL_START_HEIGHT,
             end_time: DEFAULT_VOTING_PERIOD,
             title: "test2".to_string(),
             description: "test2".to_string(),
@@ -475,8 +564,16 @@ fn query_polls() {
             deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
             execute_data: None,
             migrate_data: None,
+            execution_mode: PollExecutionMode::AllOrNothing,
+            execution_results: vec![],
+            execution_retries: 0,
+            keeper_reward_paid: Uint128::zero(),
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
+            options: vec![],
+            winning_rule: WinningRule::Plurality,
+            winning_option: None,
             staked_amount: None,
             total_balance_at_end_poll: None,
         },]
@@ -500,6 +597,8 @@ fn query_polls() {
             id: 1u64,
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
+            category: PollCategory::TreasurySpend,
+            start_height: POLL_START_HEIGHT,
             end_time: DEFAULT_VOTING_PERIOD,
             title: "test".to_string(),
             description: "test".to_string(),
@@ -507,8 +606,16 @@ fn query_polls() {
             deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
             execute_data: Some(execute_msgs),
             migrate_data: None,
+            execution_mode: PollExecutionMode::AllOrNothing,
+            execution_results: vec![],
+            execution_retries: 0,
+            keeper_reward_paid: Uint128::zero(),
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
+            options: vec![],
+            winning_rule: WinningRule::Plurality,
+            winning_option: None,
             staked_amount: None,
             total_balance_at_end_poll: None,
         }]
@@ -532,6 +639,8 @@ fn query_polls() {
             id: 2u64,
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
+            category: PollCategory::Text,
+            start_height: POLL_START_HEIGHT,
             end_time: DEFAULT_VOTING_PERIOD,
             title: "test2".to_string(),
             description: "test2".to_string(),
@@ -539,8 +648,16 @@ fn query_polls() {
             deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
             execute_data: None,
             migrate_data: None,
+            execution_mode: PollExecutionMode::AllOrNothing,
+            execution_results: vec![],
+            execution_retries: 0,
+            keeper_reward_paid: Uint128::zero(),
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
+            options: vec![],
+            winning_rule: WinningRule::Plurality,
+            winning_option: None,
             staked_amount: None,
             total_balance_at_end_poll: None,
         },]
@@ -565,7 +682,7 @@ fn query_polls() {
 fn create_poll_no_quorum() {
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
-    let env = mock_env_height(0, 0);
+    let env = mock_env_height(POLL_START_HEIGHT, 0);
     let info = mock_info(VOTING_TOKEN, &vec![]);
 
     let msg = create_poll_msg("test", "test", None, None, None);
@@ -578,7 +695,7 @@ fn create_poll_no_quorum() {
 fn fails_end_poll_before_end_height() {
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
-    let env = mock_env_height(0, 0);
+    let env = mock_env_height(POLL_START_HEIGHT, 0);
     let info = mock_info(VOTING_TOKEN, &vec![]);
 
     let msg = create_poll_msg("test", "test", None, None, None);
@@ -597,14 +714,13 @@ fn fails_end_poll_before_end_height() {
 
     match handle_res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Voting period has not expired"),
+        Err(ContractError::VotingPeriodNotExpired) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
 
 #[test]
 fn happy_days_end_poll() {
-    const POLL_START_HEIGHT: u64 = 1000;
     const POLL_ID: u64 = 1;
     let stake_amount = 1000;
 
@@ -634,19 +750,25 @@ fn happy_days_end_poll() {
     execute_msgs.push(PollExecuteMsg {
         order: 3u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz3.clone(),
+        msg: Some(exec_msg_bz3.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 2u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz2.clone(),
+        msg: Some(exec_msg_bz2.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz.clone(),
+        msg: Some(exec_msg_bz.clone()),
+        action: None,
+        funds: vec![],
     });
 
     let msg = create_poll_msg("test", "test", None, Some(execute_msgs), None);
@@ -682,7 +804,7 @@ fn happy_days_end_poll() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -731,7 +853,7 @@ fn happy_days_end_poll() {
     )
     .unwrap_err();
     match execute_res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "Poll is not in passed status"),
+        ContractError::PollNotPassed => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 
@@ -793,7 +915,7 @@ fn happy_days_end_poll() {
     )
     .unwrap_err();
     match execute_res {
-        StdError::GenericErr { msg, .. } => assert_eq!(msg, "Timelock period has not expired"),
+        ContractError::TimelockNotExpired => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 
@@ -831,21 +953,30 @@ fn happy_days_end_poll() {
     assert_eq!(
         execute_res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz.clone(),
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz2,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz3,
-                funds: vec![],
-            }))
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz.clone(),
+                    funds: vec![],
+                }),
+                100
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz2,
+                    funds: vec![],
+                }),
+                101
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz3,
+                    funds: vec![],
+                }),
+                102
+            )
         ]
     );
     assert_eq!(
@@ -913,10 +1044,10 @@ fn happy_days_end_poll() {
     let response: VotersResponse = from_binary(&res).unwrap();
     assert_eq!(response.voters.len(), 0);
 
-    // staker locked token must be disappeared
+    // staker locked token must be disappeared after the poll end
     let res = query(
         deps.as_ref(),
-        env.clone(),
+        mock_env_height(POLL_START_HEIGHT, 10000 + DEFAULT_VOTING_PERIOD),
         QueryMsg::Staker {
             address: TEST_VOTER.to_string(),
         },
@@ -942,13 +1073,10 @@ fn happy_days_end_poll() {
         }
     );
 
-    let token_manager = load_bank(
-        deps.as_ref().storage,
-        &Addr::unchecked(TEST_VOTER.to_string()),
-    )
-    .unwrap();
+    let locked_balance =
+        read_voter_poll_locks(deps.as_ref().storage, &Addr::unchecked(TEST_VOTER), 0).unwrap();
     assert_eq!(
-        token_manager.locked_balance,
+        locked_balance,
         vec![(
             1u64,
             VoterInfo {
@@ -961,7 +1089,6 @@ fn happy_days_end_poll() {
 
 #[test]
 fn fail_poll() {
-    const POLL_START_HEIGHT: u64 = 1000;
     const POLL_ID: u64 = 1;
     let stake_amount = 1000;
 
@@ -978,7 +1105,9 @@ fn fail_poll() {
     execute_msgs.push(PollExecuteMsg {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz.clone(),
+        msg: Some(exec_msg_bz.clone()),
+        action: None,
+        funds: vec![],
     });
     let msg = create_poll_msg("test", "test", None, Some(execute_msgs), None);
 
@@ -1013,7 +1142,7 @@ fn fail_poll() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -1120,11 +1249,14 @@ fn fail_poll() {
     let execute_res = execute(deps.as_mut(), creator_env, contract_info, msg).unwrap();
     assert_eq!(
         execute_res.messages,
-        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-            contract_addr: VOTING_TOKEN.to_string(),
-            msg: exec_msg_bz,
-            funds: vec![],
-        }))]
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: exec_msg_bz,
+                funds: vec![],
+            }),
+            100
+        )]
     );
     let tmp_poll_id = load_tmp_poll_id(&deps.storage).unwrap();
     assert_eq!(tmp_poll_id, 1);
@@ -1146,6 +1278,17 @@ fn fail_poll() {
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
     let poll_res: PollResponse = from_binary(&res).unwrap();
     assert_eq!(poll_res.status, PollStatus::Failed);
+    assert_eq!(
+        poll_res.execution_results,
+        vec![PollMessageResult {
+            kind: PollMessageKind::Execute,
+            order: 1,
+            contract: VOTING_TOKEN.to_string(),
+            status: PollMessageStatus::Reverted {
+                error: "Error".to_string()
+            },
+        }]
+    );
 
     let res = query(
         deps.as_ref(),
@@ -1190,21 +1333,366 @@ fn fail_poll() {
     assert!(polls_res.polls.is_empty());
 }
 
+#[test]
+fn best_effort_poll_stores_message_results() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+
+    let execute_msgs: Vec<PollExecuteMsg> = (1..=2)
+        .map(|order| PollExecuteMsg {
+            order,
+            contract: VOTING_TOKEN.to_string(),
+            msg: Some(
+                to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::new(order as u128),
+                })
+                .unwrap(),
+            ),
+            action: None,
+            funds: vec![],
+        })
+        .collect();
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: to_binary(&Cw20HookMsg::CreatePoll {
+            title: "test".to_string(),
+            description: "test".to_string(),
+            link: None,
+            execute_msgs: Some(execute_msgs.clone()),
+            migrate_msgs: None,
+            execution_mode: Some(PollExecutionMode::BestEffort),
+            category: None,
+            options: None,
+            winning_rule: None,
+        })
+        .unwrap(),
+    });
+    let mut env = mock_env_poll_start();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 1000).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ExecutePoll { poll_id: 1 },
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // every message replies, so a failed one does not revert the others
+    let msg = ExecuteMsg::Yourself {
+        yourself_msg: YourselfMsg::ExecutePollMsgs { poll_id: 1 },
+    };
+    let execute_res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        execute_msgs
+            .iter()
+            .enumerate()
+            .map(|(index, msg)| SubMsg::reply_always(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: msg.msg.clone().unwrap(),
+                    funds: vec![],
+                }),
+                100 + index as u64
+            ))
+            .collect::<Vec<SubMsg>>()
+    );
+
+    let reply_msg = Reply {
+        id: 100,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    let reply_msg = Reply {
+        id: 101,
+        result: ContractResult::Err("Error".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), reply_msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "poll_message_result"),
+            attr("poll_id", "1"),
+            attr("index", "1"),
+            attr("success", "false"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Executed);
+    assert_eq!(poll_res.execution_mode, PollExecutionMode::BestEffort);
+    assert_eq!(
+        poll_res.execution_results,
+        vec![
+            PollMessageResult {
+                kind: PollMessageKind::Execute,
+                order: 1,
+                contract: VOTING_TOKEN.to_string(),
+                status: PollMessageStatus::Success,
+            },
+            PollMessageResult {
+                kind: PollMessageKind::Execute,
+                order: 2,
+                contract: VOTING_TOKEN.to_string(),
+                status: PollMessageStatus::Failed {
+                    error: "Error".to_string()
+                },
+            },
+        ]
+    );
+}
+
+// creates a poll with one execute message and passes it, returns env at the poll end time
+fn pass_poll_with_message(deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>) -> Env {
+    stake_voting_tokens(deps, TEST_VOTER, 1000, 1000);
+    let execute_msgs = vec![PollExecuteMsg {
+        order: 1,
+        contract: VOTING_TOKEN.to_string(),
+        msg: Some(
+            to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(1),
+            })
+            .unwrap(),
+        ),
+        action: None,
+        funds: vec![],
+    }];
+    let msg = create_poll_msg("test", "test", None, Some(execute_msgs), None);
+    let mut env = mock_env_poll_start();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    cast_vote(deps, TEST_VOTER, VoteOption::Yes, 1000).unwrap();
+
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    env
+}
+
+#[test]
+fn expire_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let mut env = pass_poll_with_message(&mut deps);
+
+    let expire_msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ExpirePoll { poll_id: 1 },
+    };
+    env.block.time = env
+        .block
+        .time
+        .plus_seconds(DEFAULT_TIMELOCK_PERIOD + DEFAULT_EXPIRATION_PERIOD);
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        expire_msg.clone(),
+    );
+    match res {
+        Err(ContractError::ExpirationPeriodNotPassed) => {}
+        _ => panic!("Must return error"),
+    }
+
+    env.block.time = env.block.time.plus_seconds(1);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ExecutePoll { poll_id: 1 },
+    };
+    let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::ExecutionPeriodExpired) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), expire_msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![attr("action", "expire_poll"), attr("poll_id", "1")]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Expired),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 1);
+    assert_eq!(response.polls[0].status, PollStatus::Expired);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Passed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 0);
+}
+
+#[test]
+fn retry_failed_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::UpdateConfig {
+            quorum: None,
+            threshold: None,
+            voting_period: None,
+            timelock_period: None,
+            proposal_deposit: None,
+            snapshot_period: None,
+            unbonding_period: None,
+            expiration_period: None,
+            max_execution_retries: Some(1),
+            keeper_reward: None,
+            max_keeper_reward_per_poll: None,
+            forfeited_deposit_policy: None,
+            rejected_deposit_refund: None,
+            category_params: None,
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let mut env = pass_poll_with_message(&mut deps);
+    env.block.time = env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
+    let execute_msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ExecutePoll { poll_id: 1 },
+    };
+    let fail_reply = Reply {
+        id: POLL_EXECUTE_REPLY_ID,
+        result: ContractResult::Err("Error".to_string()),
+    };
+
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        execute_msg.clone(),
+    )
+    .unwrap();
+    reply(deps.as_mut(), mock_env(), fail_reply.clone()).unwrap();
+
+    // failed poll can be executed again
+    let execute_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        execute_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(execute_res.messages.len(), 1);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.execution_retries, 1);
+
+    let msg = ExecuteMsg::Yourself {
+        yourself_msg: YourselfMsg::ExecutePollMsgs { poll_id: 1 },
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        msg,
+    )
+    .unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Executed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 1);
+
+    // emulate revert of the poll execution
+    remove_poll_indexer(&mut deps.storage, &PollStatus::Executed, 1);
+    store_poll_indexer(&mut deps.storage, &PollStatus::Failed, 1).unwrap();
+    reply(deps.as_mut(), mock_env(), fail_reply).unwrap();
+
+    let res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), execute_msg);
+    match res {
+        Err(ContractError::NoExecutionRetriesLeft) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
 #[test]
 fn end_poll_zero_quorum() {
     let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
     mock_init(&mut deps);
-    let mut creator_env = mock_env_height(1000, 10000);
+    let mut creator_env = mock_env_height(POLL_START_HEIGHT, 10000);
     let mut creator_info = mock_info(VOTING_TOKEN, &vec![]);
 
     let mut execute_msgs: Vec<PollExecuteMsg> = vec![];
     execute_msgs.push(PollExecuteMsg {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: to_binary(&Cw20ExecuteMsg::Burn {
-            amount: Uint128::new(123),
-        })
-        .unwrap(),
+        msg: Some(
+            to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(123),
+            })
+            .unwrap(),
+        ),
+        action: None,
+        funds: vec![],
     });
 
     let msg = create_poll_msg("test", "test", None, Some(execute_msgs), None);
@@ -1239,7 +1727,7 @@ fn end_poll_zero_quorum() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let info = mock_info(VOTING_TOKEN, &[]);
@@ -1266,6 +1754,7 @@ fn end_poll_zero_quorum() {
             attr("poll_id", "1"),
             attr("rejected_reason", "Quorum not reached"),
             attr("passed", "false"),
+            attr("forfeited_deposit", DEFAULT_PROPOSAL_DEPOSIT.to_string()),
         ]
     );
 
@@ -1321,7 +1810,7 @@ fn end_poll_quorum_rejected() {
     mock_init(&mut deps);
 
     let msg = create_poll_msg("test", "test", None, None, None);
-    let mut creator_env = mock_env();
+    let mut creator_env = mock_env_poll_start();
     let mut creator_info = mock_info(VOTING_TOKEN, &vec![]);
     let execute_res = execute(
         deps.as_mut(),
@@ -1360,7 +1849,7 @@ fn end_poll_quorum_rejected() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let info = mock_info(VOTING_TOKEN, &[]);
@@ -1410,6 +1899,7 @@ fn end_poll_quorum_rejected() {
             attr("poll_id", "1"),
             attr("rejected_reason", "Quorum not reached"),
             attr("passed", "false"),
+            attr("forfeited_deposit", DEFAULT_PROPOSAL_DEPOSIT.to_string()),
         ]
     );
 }
@@ -1420,7 +1910,7 @@ fn end_poll_quorum_rejected_noting_staked() {
     mock_init(&mut deps);
 
     let msg = create_poll_msg("test", "test", None, None, None);
-    let mut creator_env = mock_env();
+    let mut creator_env = mock_env_poll_start();
     let mut creator_info = mock_info(VOTING_TOKEN, &vec![]);
     let execute_res = execute(
         deps.as_mut(),
@@ -1467,6 +1957,7 @@ fn end_poll_quorum_rejected_noting_staked() {
             attr("poll_id", "1"),
             attr("rejected_reason", "Quorum not reached"),
             attr("passed", "false"),
+            attr("forfeited_deposit", DEFAULT_PROPOSAL_DEPOSIT.to_string()),
         ]
     );
 }
@@ -1477,7 +1968,7 @@ fn end_poll_nay_rejected() {
     let voter2_stake = 1000;
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
-    let mut creator_env = mock_env();
+    let mut creator_env = mock_env_poll_start();
     let mut creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
 
     let msg = create_poll_msg("test", "test", None, None, None);
@@ -1518,7 +2009,7 @@ fn end_poll_nay_rejected() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(voter1_stake as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let info = mock_info(VOTING_TOKEN, &[]);
@@ -1543,7 +2034,7 @@ fn end_poll_nay_rejected() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER_2.to_string(),
         amount: Uint128::from(voter2_stake as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let info = mock_info(VOTING_TOKEN, &[]);
@@ -1593,15 +2084,189 @@ fn end_poll_nay_rejected() {
 }
 
 #[test]
-fn fails_cast_vote_not_enough_staked() {
+fn end_poll_abstain_excluded_from_threshold() {
+    let voter1_stake = 100;
+    let voter2_stake = 1000;
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
-    let env = mock_env_height(0, 0);
-    let info = mock_info(VOTING_TOKEN, &vec![]);
+    let mut creator_env = mock_env_poll_start();
+    let creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
 
     let msg = create_poll_msg("test", "test", None, None, None);
+    execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
 
-    let execute_res = execute(deps.as_mut(), env, info, msg.clone()).unwrap();
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::new(voter1_stake + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(voter1_stake),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::new(voter1_stake + voter2_stake + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER_2.to_string(),
+        amount: Uint128::from(voter2_stake),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CastVote {
+            poll_id: 1,
+            vote: VoteOption::Yes,
+            amount: Uint128::from(voter1_stake),
+        },
+    };
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_cast_vote_success(TEST_VOTER, voter1_stake, 1, VoteOption::Yes, execute_res);
+
+    let info = mock_info(TEST_VOTER_2, &[]);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CastVote {
+            poll_id: 1,
+            vote: VoteOption::Abstain,
+            amount: Uint128::from(voter2_stake),
+        },
+    };
+    let execute_res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_cast_vote_success(
+        TEST_VOTER_2,
+        voter2_stake,
+        1,
+        VoteOption::Abstain,
+        execute_res,
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Uint128::from(voter1_stake), value.yes_votes);
+    assert_eq!(Uint128::zero(), value.no_votes);
+    assert_eq!(Uint128::from(voter2_stake), value.abstain_votes);
+
+    // abstain votes count toward quorum, but only yes/no are used for threshold
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env,
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
+        ]
+    );
+}
+
+#[test]
+fn end_poll_only_abstain_votes_rejected() {
+    let stake_amount = 1000;
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let mut creator_env = mock_env_poll_start();
+    let creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
+
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap();
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::new(stake_amount + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER.to_string(),
+        amount: Uint128::from(stake_amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
+    });
+    let info = mock_info(VOTING_TOKEN, &[]);
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(TEST_VOTER, &[]);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CastVote {
+            poll_id: 1,
+            vote: VoteOption::Abstain,
+            amount: Uint128::from(stake_amount),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    creator_env.block.time = creator_env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        creator_env,
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Threshold not reached"),
+            attr("passed", "false"),
+        ]
+    );
+
+    // quorum is reached, so deposit is refunded
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+}
+
+#[test]
+fn fails_cast_vote_not_enough_staked() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let env = mock_env_height(POLL_START_HEIGHT, 0);
+    let info = mock_info(VOTING_TOKEN, &vec![]);
+
+    let msg = create_poll_msg("test", "test", None, None, None);
+
+    let execute_res = execute(deps.as_mut(), env, info, msg.clone()).unwrap();
     assert_create_poll_result(1, DEFAULT_VOTING_PERIOD, TEST_CREATOR, execute_res, &deps);
 
     deps.querier.with_token_balances(&[(
@@ -1615,7 +2280,7 @@ fn fails_cast_vote_not_enough_staked() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(10u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -1623,7 +2288,7 @@ fn fails_cast_vote_not_enough_staked() {
     let execute_res = execute(deps.as_mut(), env, info, msg.clone()).unwrap();
     assert_stake_tokens_result(10, DEFAULT_PROPOSAL_DEPOSIT, 10, 1, execute_res, &deps);
 
-    let env = mock_env_height(0, 0);
+    let env = mock_env_height(POLL_START_HEIGHT, 0);
     let info = mock_info(TEST_VOTER, &coins(11, VOTING_TOKEN));
     let msg = ExecuteMsg::Anyone {
         anyone_msg: AnyoneMsg::CastVote {
@@ -1637,9 +2302,7 @@ fn fails_cast_vote_not_enough_staked() {
 
     match res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(msg, "User does not have enough staked tokens.")
-        }
+        Err(ContractError::InsufficientStake) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -1649,7 +2312,7 @@ fn happy_days_cast_vote() {
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
 
-    let env = mock_env_height(0, 0);
+    let env = mock_env_height(POLL_START_HEIGHT, 0);
     let info = mock_info(VOTING_TOKEN, &vec![]);
     let msg = create_poll_msg("test", "test", None, None, None);
 
@@ -1667,7 +2330,7 @@ fn happy_days_cast_vote() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -1675,7 +2338,7 @@ fn happy_days_cast_vote() {
     let execute_res = execute(deps.as_mut(), env, info, msg.clone()).unwrap();
     assert_stake_tokens_result(11, DEFAULT_PROPOSAL_DEPOSIT, 11, 1, execute_res, &deps);
 
-    let env = mock_env_height(0, 10000);
+    let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(TEST_VOTER, &coins(11, VOTING_TOKEN));
     let amount = 10u128;
     let msg = ExecuteMsg::Anyone {
@@ -1697,11 +2360,12 @@ fn happy_days_cast_vote() {
             &Uint128::new(22u128 + DEFAULT_PROPOSAL_DEPOSIT),
         )],
     )]);
+    add_rewards(&mut deps, 11u128);
 
     // Query staker
     let res = query(
         deps.as_ref(),
-        mock_env(),
+        mock_env_height(POLL_START_HEIGHT, 10000),
         QueryMsg::Staker {
             address: TEST_VOTER.to_string(),
         },
@@ -1773,7 +2437,7 @@ fn happy_days_withdraw_voting_tokens() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -1787,7 +2451,10 @@ fn happy_days_withdraw_voting_tokens() {
         State {
             poll_count: 0,
             total_share: Uint128::from(11u128),
+            total_staked: Uint128::from(11u128),
             total_deposit: Uint128::zero(),
+            total_unbonding: Uint128::zero(),
+            keeper_reward_pool: Uint128::zero(),
         }
     );
 
@@ -1796,6 +2463,7 @@ fn happy_days_withdraw_voting_tokens() {
         &VOTING_TOKEN.to_string(),
         &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(22u128))],
     )]);
+    add_rewards(&mut deps, 11u128);
 
     let env = mock_env();
     let info = mock_info(TEST_VOTER, &[]);
@@ -1827,7 +2495,10 @@ fn happy_days_withdraw_voting_tokens() {
         State {
             poll_count: 0,
             total_share: Uint128::from(6u128),
+            total_staked: Uint128::from(11u128),
             total_deposit: Uint128::zero(),
+            total_unbonding: Uint128::zero(),
+            keeper_reward_pool: Uint128::zero(),
         }
     );
 }
@@ -1845,7 +2516,7 @@ fn happy_days_withdraw_voting_tokens_all() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -1859,7 +2530,10 @@ fn happy_days_withdraw_voting_tokens_all() {
         State {
             poll_count: 0,
             total_share: Uint128::from(11u128),
+            total_staked: Uint128::from(11u128),
             total_deposit: Uint128::zero(),
+            total_unbonding: Uint128::zero(),
+            keeper_reward_pool: Uint128::zero(),
         }
     );
 
@@ -1868,6 +2542,7 @@ fn happy_days_withdraw_voting_tokens_all() {
         &VOTING_TOKEN.to_string(),
         &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(22u128))],
     )]);
+    add_rewards(&mut deps, 11u128);
 
     let env = mock_env();
     let info = mock_info(TEST_VOTER, &[]);
@@ -1897,155 +2572,94 @@ fn happy_days_withdraw_voting_tokens_all() {
         State {
             poll_count: 0,
             total_share: Uint128::zero(),
+            total_staked: Uint128::zero(),
             total_deposit: Uint128::zero(),
+            total_unbonding: Uint128::zero(),
+            keeper_reward_pool: Uint128::zero(),
         }
     );
 }
 
 #[test]
-fn withdraw_voting_tokens_remove_not_in_progress_poll_voter_info() {
+fn withdraw_voting_tokens_locked_till_poll_end() {
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+
+    let start_time = mock_env().block.time.seconds();
+    for (height, time) in [
+        (POLL_START_HEIGHT, start_time),
+        (POLL_START_HEIGHT + 1, start_time + 10),
+    ] {
+        let msg = create_poll_msg("test", "test", None, None, None);
+        execute(
+            deps.as_mut(),
+            mock_env_height(height, time),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
 
-    deps.querier.with_token_balances(&[(
-        &VOTING_TOKEN.to_string(),
-        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(11u128))],
-    )]);
-
-    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
-        sender: TEST_VOTER.to_string(),
-        amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
-    });
-
-    let env = mock_env();
-    let info = mock_info(VOTING_TOKEN, &[]);
-    let execute_res = execute(deps.as_mut(), env, info, msg.clone()).unwrap();
-    assert_stake_tokens_result(11, 0, 11, 0, execute_res, &mut deps);
-
-    // make fake polls; one in progress & one in passed
-    store_poll(
-        deps.as_mut().storage,
-        1u64,
-        &Poll {
-            id: 1u64,
-            creator: Addr::unchecked(""),
-            status: PollStatus::InProgress,
-            yes_votes: Uint128::zero(),
-            no_votes: Uint128::zero(),
-            end_time: 0u64,
-            title: "title".to_string(),
-            description: "description".to_string(),
-            deposit_amount: Uint128::zero(),
-            link: None,
-            execute_data: None,
-            migrate_data: None,
-            total_balance_at_end_poll: None,
-            staked_amount: None,
-        },
-    )
-    .unwrap();
-
-    store_poll(
-        deps.as_mut().storage,
-        2u64,
-        &Poll {
-            id: 1u64,
-            creator: Addr::unchecked(""),
-            status: PollStatus::Passed,
-            yes_votes: Uint128::zero(),
-            no_votes: Uint128::zero(),
-            end_time: 0u64,
-            title: "title".to_string(),
-            description: "description".to_string(),
-            deposit_amount: Uint128::zero(),
-            link: None,
-            execute_data: None,
-            migrate_data: None,
-            total_balance_at_end_poll: None,
-            staked_amount: None,
-        },
-    )
-    .unwrap();
-
-    let voter_addr = Addr::unchecked(TEST_VOTER);
-    store_poll_voter(
-        &mut deps.storage,
-        1u64,
-        &voter_addr,
-        &VoterInfo {
-            vote: VoteOption::Yes,
-            balance: Uint128::new(5u128),
-        },
-    )
-    .unwrap();
-    store_poll_voter(
-        deps.as_mut().storage,
-        2u64,
-        &voter_addr,
-        &VoterInfo {
-            vote: VoteOption::Yes,
-            balance: Uint128::new(5u128),
-        },
-    )
-    .unwrap();
+    let env = mock_env_height(POLL_START_HEIGHT + 1, start_time + 10);
+    for (poll_id, amount) in [(1u64, 60u128), (2, 30)] {
+        let msg = ExecuteMsg::Anyone {
+            anyone_msg: AnyoneMsg::CastVote {
+                poll_id,
+                vote: VoteOption::Yes,
+                amount: Uint128::new(amount),
+            },
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    }
 
-    store_bank(
-        deps.as_mut().storage,
-        &voter_addr,
-        &TokenManager {
-            share: Uint128::new(11u128),
-            locked_balance: vec![
-                (
-                    1u64,
-                    VoterInfo {
-                        vote: VoteOption::Yes,
-                        balance: Uint128::new(5u128),
-                    },
-                ),
-                (
-                    2u64,
-                    VoterInfo {
-                        vote: VoteOption::Yes,
-                        balance: Uint128::new(5u128),
-                    },
-                ),
-            ],
-        },
-    )
-    .unwrap();
-
-    // withdraw voting token must remove not in-progress votes infos from the store
-    let env = mock_env();
-    let info = mock_info(TEST_VOTER, &[]);
-    let msg = ExecuteMsg::Anyone {
+    let withdraw_msg = |amount: u128| ExecuteMsg::Anyone {
         anyone_msg: AnyoneMsg::WithdrawVotingTokens {
-            amount: Some(Uint128::from(5u128)),
+            amount: Some(Uint128::new(amount)),
         },
     };
+    let info = mock_info(TEST_VOTER, &[]);
+    match execute(deps.as_mut(), env, info.clone(), withdraw_msg(41)) {
+        Err(ContractError::WithdrawTooMany) => {}
+        _ => panic!("Must return error"),
+    }
 
-    execute(deps.as_mut(), env, info, msg).unwrap();
-    let voter = load_poll_voter(&deps.storage, 1u64, &voter_addr).unwrap();
-    assert_eq!(
-        voter,
-        VoterInfo {
-            vote: VoteOption::Yes,
-            balance: Uint128::new(5u128),
-        }
+    // the first poll ended, only the vote in the second one is locked
+    let env = mock_env_height(
+        POLL_START_HEIGHT + 2,
+        start_time + DEFAULT_VOTING_PERIOD + 1,
     );
-    assert!(load_poll_voter(&deps.storage, 2u64, &voter_addr).is_err());
-
-    let token_manager = load_bank(deps.as_ref().storage, &voter_addr).unwrap();
+    let res = query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
     assert_eq!(
-        token_manager.locked_balance,
+        response.locked_balance,
         vec![(
-            1u64,
+            2u64,
             VoterInfo {
                 vote: VoteOption::Yes,
-                balance: Uint128::new(5u128),
+                balance: Uint128::new(30),
             }
         )]
     );
+    match execute(deps.as_mut(), env.clone(), info.clone(), withdraw_msg(71)) {
+        Err(ContractError::WithdrawTooMany) => {}
+        _ => panic!("Must return error"),
+    }
+    execute(deps.as_mut(), env.clone(), info.clone(), withdraw_msg(40)).unwrap();
+
+    // retracted vote unlocks tokens before the poll end
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::RetractVote { poll_id: 2 },
+    };
+    execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    execute(deps.as_mut(), env, info, withdraw_msg(60)).unwrap();
 }
 
 #[test]
@@ -2065,7 +2679,7 @@ fn fails_withdraw_voting_tokens_no_stake() {
 
     match res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Nothing staked"),
+        Err(ContractError::NothingStaked) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -2083,7 +2697,7 @@ fn fails_withdraw_too_many_tokens() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(10u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -2103,9 +2717,7 @@ fn fails_withdraw_too_many_tokens() {
 
     match execute_res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => {
-            assert_eq!(msg, "User is trying to withdraw too many tokens.")
-        }
+        Err(ContractError::WithdrawTooMany) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -2115,7 +2727,7 @@ fn fails_cast_vote_twice() {
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
 
-    let env = mock_env_height(0, 10000);
+    let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
 
     let msg = create_poll_msg("test", "test", None, None, None);
@@ -2140,7 +2752,7 @@ fn fails_cast_vote_twice() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -2156,7 +2768,7 @@ fn fails_cast_vote_twice() {
             amount: Uint128::from(amount),
         },
     };
-    let env = mock_env_height(0, 10000);
+    let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(TEST_VOTER, &[]);
     let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_cast_vote_success(TEST_VOTER, amount, 1, VoteOption::Yes, execute_res);
@@ -2172,7 +2784,7 @@ fn fails_cast_vote_twice() {
 
     match execute_res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "User has already voted."),
+        Err(ContractError::AlreadyVoted) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -2196,7 +2808,7 @@ fn fails_cast_vote_without_poll() {
 
     match execute_res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Poll does not exist"),
+        Err(ContractError::PollNotFound) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -2214,7 +2826,7 @@ fn happy_days_stake_voting_tokens() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -2234,7 +2846,7 @@ fn fails_insufficient_funds() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(0u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -2243,7 +2855,7 @@ fn fails_insufficient_funds() {
 
     match execute_res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg, .. }) => assert_eq!(msg, "Insufficient funds sent"),
+        Err(ContractError::InsufficientFunds) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -2264,7 +2876,7 @@ fn fails_staking_wrong_token() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -2273,7 +2885,7 @@ fn fails_staking_wrong_token() {
 
     match execute_res {
         Ok(_) => panic!("Must return error"),
-        Err(StdError::GenericErr { msg }) => assert_eq!(msg, "unauthorized"),
+        Err(ContractError::Unauthorized) => {}
         Err(e) => panic!("Unexpected error: {:?}", e),
     }
 }
@@ -2294,7 +2906,7 @@ fn share_calculation() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -2309,11 +2921,12 @@ fn share_calculation() {
             &Uint128::new(200u128 + 100u128),
         )],
     )]);
+    add_rewards(&mut deps, 100u128);
 
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(100u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -2372,7 +2985,7 @@ fn assert_create_poll_result(
     end_time: u64,
     creator: &str,
     handle_res: Response,
-    deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    deps: &OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
 ) {
     assert_eq!(
         handle_res.attributes,
@@ -2391,7 +3004,10 @@ fn assert_create_poll_result(
         State {
             poll_count: 1,
             total_share: Uint128::zero(),
+            total_staked: Uint128::zero(),
             total_deposit: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+            total_unbonding: Uint128::zero(),
+            keeper_reward_pool: Uint128::zero(),
         }
     );
 }
@@ -2402,7 +3018,7 @@ fn assert_stake_tokens_result(
     new_share: u128,
     poll_count: u64,
     execute_res: Response,
-    deps: &OwnedDeps<MockStorage, MockApi, WasmMockQuerier>,
+    deps: &OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
 ) {
     assert_eq!(
         execute_res.attributes.get(2).expect("no log"),
@@ -2415,7 +3031,10 @@ fn assert_stake_tokens_result(
         State {
             poll_count,
             total_share: Uint128::new(total_share),
+            total_staked: Uint128::new(total_share),
             total_deposit: Uint128::new(total_deposit),
+            total_unbonding: Uint128::zero(),
+            keeper_reward_pool: Uint128::zero(),
         }
     );
 }
@@ -2447,25 +3066,37 @@ fn update_config() {
     // update owner
     let env = mock_env();
     let info = mock_info(TEST_CREATOR, &[]);
-    let msg = ExecuteMsg::Governance {
-        governance_msg: GovernanceMsg::UpdateConfig {
-            owner: Some("addr0001".to_string()),
-            quorum: None,
-            threshold: None,
-            voting_period: None,
-            timelock_period: None,
-            proposal_deposit: None,
-            snapshot_period: None,
+    let msg = ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::ProposeNewOwner {
+            owner: "addr0001".to_string(),
+            expires_in: None,
         },
     };
 
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
     assert!(execute_res.messages.is_empty());
 
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(TEST_CREATOR, config.owner.as_str());
+    assert_eq!(
+        config.pending_owner,
+        Some(PendingOwner {
+            owner: Addr::unchecked("addr0001"),
+            expires_at: None,
+        })
+    );
+
+    let msg = ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::AcceptOwnership {},
+    };
+    execute(deps.as_mut(), mock_env(), mock_info("addr0001", &[]), msg).unwrap();
+
     // it worked, let's query the state
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
     let config: ConfigResponse = from_binary(&res).unwrap();
     assert_eq!("addr0001", config.owner.as_str());
+    assert_eq!(None, config.pending_owner);
     assert_eq!(Decimal::percent(DEFAULT_QUORUM), config.quorum);
     assert_eq!(Decimal::percent(DEFAULT_THRESHOLD), config.threshold);
     assert_eq!(DEFAULT_VOTING_PERIOD, config.voting_period);
@@ -2477,13 +3108,20 @@ fn update_config() {
     let info = mock_info("addr0001", &[]);
     let msg = ExecuteMsg::Governance {
         governance_msg: GovernanceMsg::UpdateConfig {
-            owner: None,
             quorum: Some(Decimal::percent(20)),
             threshold: Some(Decimal::percent(75)),
             voting_period: Some(20000u64),
             timelock_period: Some(20000u64),
             proposal_deposit: Some(Uint128::new(123u128)),
             snapshot_period: Some(11),
+            unbonding_period: Some(100),
+            expiration_period: Some(30000),
+            max_execution_retries: Some(2),
+            keeper_reward: None,
+            max_keeper_reward_per_poll: None,
+            forfeited_deposit_policy: None,
+            rejected_deposit_refund: None,
+            category_params: None,
         },
     };
 
@@ -2500,25 +3138,35 @@ fn update_config() {
     assert_eq!(20000u64, config.timelock_period);
     assert_eq!(123u128, config.proposal_deposit.u128());
     assert_eq!(11u64, config.snapshot_period);
+    assert_eq!(100u64, config.unbonding_period);
+    assert_eq!(30000u64, config.expiration_period);
+    assert_eq!(2u64, config.max_execution_retries);
 
     // Unauthorzied err
     let env = mock_env();
     let info = mock_info(TEST_CREATOR, &[]);
     let msg = ExecuteMsg::Governance {
         governance_msg: GovernanceMsg::UpdateConfig {
-            owner: None,
             quorum: None,
             threshold: None,
             voting_period: None,
             timelock_period: None,
             proposal_deposit: None,
             snapshot_period: None,
+            unbonding_period: None,
+            expiration_period: None,
+            max_execution_retries: None,
+            keeper_reward: None,
+            max_keeper_reward_per_poll: None,
+            forfeited_deposit_policy: None,
+            rejected_deposit_refund: None,
+            category_params: None,
         },
     };
 
     let execute_res = execute(deps.as_mut(), env, info, msg);
     match execute_res {
-        Err(StdError::GenericErr { msg }) => assert_eq!(msg, "unauthorized"),
+        Err(ContractError::Unauthorized) => {}
         _ => panic!("Must return unauthorized error"),
     }
 }
@@ -2527,7 +3175,7 @@ fn update_config() {
 fn add_several_execute_msgs() {
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
-    let env = mock_env_height(0, 10000);
+    let env = mock_env_height(POLL_START_HEIGHT, 10000);
     let info = mock_info(VOTING_TOKEN, &vec![]);
 
     let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
@@ -2551,19 +3199,25 @@ fn add_several_execute_msgs() {
     execute_msgs.push(PollExecuteMsg {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz.clone(),
+        msg: Some(exec_msg_bz.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 3u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz3.clone(),
+        msg: Some(exec_msg_bz3.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 2u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz2.clone(),
+        msg: Some(exec_msg_bz2.clone()),
+        action: None,
+        funds: vec![],
     });
 
     let msg = create_poll_msg("test", "test", None, Some(execute_msgs.clone()), None);
@@ -2587,7 +3241,6 @@ fn add_several_execute_msgs() {
 
 #[test]
 fn execute_poll_with_order() {
-    const POLL_START_HEIGHT: u64 = 1000;
     const POLL_ID: u64 = 1;
     let stake_amount = 1000;
 
@@ -2633,31 +3286,41 @@ fn execute_poll_with_order() {
     execute_msgs.push(PollExecuteMsg {
         order: 3u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz3.clone(),
+        msg: Some(exec_msg_bz3.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 4u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz4.clone(),
+        msg: Some(exec_msg_bz4.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 2u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz2.clone(),
+        msg: Some(exec_msg_bz2.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 5u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz5.clone(),
+        msg: Some(exec_msg_bz5.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz.clone(),
+        msg: Some(exec_msg_bz.clone()),
+        action: None,
+        funds: vec![],
     });
 
     //and migrate messages
@@ -2744,6 +3407,8 @@ fn execute_poll_with_order() {
             id: 1u64,
             creator: TEST_CREATOR.to_string(),
             status: PollStatus::InProgress,
+            category: PollCategory::CodeMigration,
+            start_height: creator_env.block.height,
             end_time: creator_env
                 .block
                 .time
@@ -2755,8 +3420,16 @@ fn execute_poll_with_order() {
             deposit_amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
             execute_data: Some(execute_msgs),
             migrate_data: Some(migrate_msgs),
+            execution_mode: PollExecutionMode::AllOrNothing,
+            execution_results: vec![],
+            execution_retries: 0,
+            keeper_reward_paid: Uint128::zero(),
             yes_votes: Uint128::zero(),
             no_votes: Uint128::zero(),
+            abstain_votes: Uint128::zero(),
+            options: vec![],
+            winning_rule: WinningRule::Plurality,
+            winning_option: None,
             staked_amount: None,
             total_balance_at_end_poll: None,
         }]
@@ -2773,7 +3446,7 @@ fn execute_poll_with_order() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let info = mock_info(VOTING_TOKEN, &[]);
@@ -2882,56 +3555,86 @@ fn execute_poll_with_order() {
     assert_eq!(
         execute_res.messages,
         vec![
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz2,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz3,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz4,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: exec_msg_bz5,
-                funds: vec![],
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Migrate {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: migrate_msg_bz,
-                new_code_id: 11
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Migrate {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: migrate_msg_bz2,
-                new_code_id: 11
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Migrate {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: migrate_msg_bz3,
-                new_code_id: 11
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Migrate {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: migrate_msg_bz4,
-                new_code_id: 11
-            })),
-            SubMsg::new(CosmosMsg::Wasm(WasmMsg::Migrate {
-                contract_addr: VOTING_TOKEN.to_string(),
-                msg: migrate_msg_bz5,
-                new_code_id: 11
-            })),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz,
+                    funds: vec![],
+                }),
+                100
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz2,
+                    funds: vec![],
+                }),
+                101
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz3,
+                    funds: vec![],
+                }),
+                102
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz4,
+                    funds: vec![],
+                }),
+                103
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: exec_msg_bz5,
+                    funds: vec![],
+                }),
+                104
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Migrate {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: migrate_msg_bz,
+                    new_code_id: 11
+                }),
+                105
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Migrate {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: migrate_msg_bz2,
+                    new_code_id: 11
+                }),
+                106
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Migrate {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: migrate_msg_bz3,
+                    new_code_id: 11
+                }),
+                107
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Migrate {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: migrate_msg_bz4,
+                    new_code_id: 11
+                }),
+                108
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Migrate {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: migrate_msg_bz5,
+                    new_code_id: 11
+                }),
+                109
+            ),
         ]
     );
     assert_eq!(
@@ -2948,9 +3651,10 @@ fn snapshot_poll() {
 
     let mut deps = mock_dependencies(&coins(100, VOTING_TOKEN));
     mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, stake_amount, stake_amount);
 
     let msg = create_poll_msg("test", "test", None, None, None);
-    let mut creator_env = mock_env();
+    let mut creator_env = mock_env_poll_start();
     let creator_info = mock_info(VOTING_TOKEN, &vec![]);
     let execute_res = execute(
         deps.as_mut(),
@@ -2981,10 +3685,7 @@ fn snapshot_poll() {
         },
     )
     .unwrap_err();
-    assert_eq!(
-        StdError::generic_err("Cannot snapshot at this time",),
-        snapshot_err
-    );
+    assert_eq!(ContractError::SnapshotNotAllowed, snapshot_err);
 
     // change time
     creator_env.block.time = poll_end_time.minus_seconds(10);
@@ -3026,10 +3727,7 @@ fn snapshot_poll() {
         },
     )
     .unwrap_err();
-    assert_eq!(
-        StdError::generic_err("Snapshot has already occurred"),
-        snapshot_error
-    );
+    assert_eq!(ContractError::SnapshotAlreadyOccurred, snapshot_error);
 }
 
 #[test]
@@ -3037,7 +3735,7 @@ fn happy_days_cast_vote_with_snapshot() {
     let mut deps = mock_dependencies(&[]);
     mock_init(&mut deps);
 
-    let env = mock_env_height(0, 0);
+    let env = mock_env_height(POLL_START_HEIGHT, 0);
     let info = mock_info(VOTING_TOKEN, &vec![]);
     let msg = create_poll_msg("test", "test", None, None, None);
 
@@ -3055,7 +3753,7 @@ fn happy_days_cast_vote_with_snapshot() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -3064,7 +3762,7 @@ fn happy_days_cast_vote_with_snapshot() {
     assert_stake_tokens_result(11, DEFAULT_PROPOSAL_DEPOSIT, 11, 1, execute_res, &deps);
 
     //cast_vote without snapshot
-    let env = mock_env_height(0, 0);
+    let env = mock_env_height(POLL_START_HEIGHT, 0);
     let info = mock_info(TEST_VOTER, &coins(11, VOTING_TOKEN));
     let amount = 10u128;
 
@@ -3097,7 +3795,7 @@ fn happy_days_cast_vote_with_snapshot() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER_2.to_string(),
         amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -3112,7 +3810,7 @@ fn happy_days_cast_vote_with_snapshot() {
             amount: Uint128::from(10u128),
         },
     };
-    let env = mock_env_height(0, end_time - 9);
+    let env = mock_env_height(POLL_START_HEIGHT, end_time - 9);
     let info = mock_info(TEST_VOTER_2, &[]);
     let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_cast_vote_success(TEST_VOTER_2, amount, 1, VoteOption::Yes, execute_res);
@@ -3131,10 +3829,7 @@ fn happy_days_cast_vote_with_snapshot() {
         },
     )
     .unwrap_err();
-    assert_eq!(
-        StdError::generic_err("Snapshot has already occurred"),
-        snap_error
-    );
+    assert_eq!(ContractError::SnapshotAlreadyOccurred, snap_error);
 
     // balance be double
     deps.querier.with_token_balances(&[(
@@ -3149,7 +3844,7 @@ fn happy_days_cast_vote_with_snapshot() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER_3.to_string(),
         amount: Uint128::from(11u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -3162,7 +3857,7 @@ fn happy_days_cast_vote_with_snapshot() {
             amount: Uint128::from(10u128),
         },
     };
-    let env = mock_env_height(0, end_time - 8);
+    let env = mock_env_height(POLL_START_HEIGHT, end_time - 8);
     let info = mock_info(TEST_VOTER_3, &[]);
     let execute_res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
     assert_cast_vote_success(TEST_VOTER_3, amount, 1, VoteOption::Yes, execute_res);
@@ -3173,7 +3868,7 @@ fn happy_days_cast_vote_with_snapshot() {
 }
 
 #[test]
-fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
+fn end_poll_quorum_not_inflated_by_tokens_staked_after_poll_creation() {
     const POLL_START_TIME: u64 = 1000;
     const POLL_ID: u64 = 1;
     let stake_amount = 1000;
@@ -3181,7 +3876,7 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
     let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
     mock_init(&mut deps);
 
-    let mut creator_env = mock_env_height(0, POLL_START_TIME);
+    let mut creator_env = mock_env_height(POLL_START_HEIGHT, POLL_START_TIME);
     let mut creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
 
     let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
@@ -3194,13 +3889,17 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
     execute_msgs.push(PollExecuteMsg {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz.clone(),
+        msg: Some(exec_msg_bz.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 2u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz.clone(),
+        msg: Some(exec_msg_bz.clone()),
+        action: None,
+        funds: vec![],
     });
 
     let msg = create_poll_msg("test", "test", None, Some(execute_msgs), None);
@@ -3236,7 +3935,7 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -3258,7 +3957,7 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
             amount: Uint128::from(stake_amount),
         },
     };
-    let env = mock_env_height(0, POLL_START_TIME);
+    let env = mock_env_height(POLL_START_HEIGHT, POLL_START_TIME);
     let info = mock_info(TEST_VOTER, &[]);
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
 
@@ -3288,19 +3987,20 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
             &Uint128::new(((10 * stake_amount) + DEFAULT_PROPOSAL_DEPOSIT) as u128),
         )],
     )]);
+    add_rewards(&mut deps, stake_amount);
 
-    //cast another vote
+    // another voter stakes after the poll creation block
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER_2.to_string(),
         amount: Uint128::from(8 * stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
-    let env = mock_env();
+    let env = mock_env_height(POLL_START_HEIGHT + 1, POLL_START_TIME);
     let info = mock_info(VOTING_TOKEN, &[]);
     execute(deps.as_mut(), env, info, msg.clone()).unwrap();
 
-    // another voter cast a vote
+    // and has no voting power in the poll
     let msg = ExecuteMsg::Anyone {
         anyone_msg: AnyoneMsg::CastVote {
             poll_id: 1,
@@ -3308,25 +4008,18 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
             amount: Uint128::from(stake_amount),
         },
     };
-    let env = mock_env_height(creator_env.block.height, 10000);
+    let env = mock_env_height(POLL_START_HEIGHT + 1, 10000);
     let info = mock_info(TEST_VOTER_2, &[]);
-    let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
-
-    assert_eq!(
-        execute_res.attributes,
-        vec![
-            attr("action", "cast_vote"),
-            attr("poll_id", POLL_ID.to_string()),
-            attr("amount", "1000"),
-            attr("voter", TEST_VOTER_2),
-            attr("vote_option", "yes"),
-        ]
-    );
+    let res = execute(deps.as_mut(), env, info, msg);
+    match res {
+        Err(ContractError::InsufficientStake) => {}
+        _ => panic!("Must return error"),
+    }
 
     creator_info.sender = Addr::unchecked(TEST_CREATOR);
     creator_env.block.time = creator_env.block.time.plus_seconds(10);
 
-    // quorum must reach
+    // quorum is calculated only with tokens staked before the poll creation
     let msg = ExecuteMsg::Anyone {
         anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
     };
@@ -3343,15 +4036,16 @@ fn fails_end_poll_quorum_inflation_without_snapshot_poll() {
         vec![
             attr("action", "end_poll"),
             attr("poll_id", "1"),
-            attr("rejected_reason", "Quorum not reached"),
-            attr("passed", "false"),
+            attr("rejected_reason", ""),
+            attr("passed", "true"),
         ]
     );
 
+    // voter1 share is worth twice more after the balance increase
     let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
     let value: PollResponse = from_binary(&res).unwrap();
     assert_eq!(
-        10 * stake_amount,
+        2 * stake_amount,
         value.total_balance_at_end_poll.unwrap().u128()
     );
 }
@@ -3365,7 +4059,7 @@ fn happy_days_end_poll_with_controlled_quorum() {
     let mut deps = mock_dependencies(&coins(1000, VOTING_TOKEN));
     mock_init(&mut deps);
 
-    let mut creator_env = mock_env_height(0, POLL_START_TIME);
+    let mut creator_env = mock_env_height(POLL_START_HEIGHT, POLL_START_TIME);
     let mut creator_info = mock_info(VOTING_TOKEN, &coins(2, VOTING_TOKEN));
 
     let exec_msg_bz = to_binary(&Cw20ExecuteMsg::Burn {
@@ -3378,13 +4072,17 @@ fn happy_days_end_poll_with_controlled_quorum() {
     execute_msgs.push(PollExecuteMsg {
         order: 1u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz.clone(),
+        msg: Some(exec_msg_bz.clone()),
+        action: None,
+        funds: vec![],
     });
 
     execute_msgs.push(PollExecuteMsg {
         order: 2u64,
         contract: VOTING_TOKEN.to_string(),
-        msg: exec_msg_bz.clone(),
+        msg: Some(exec_msg_bz.clone()),
+        action: None,
+        funds: vec![],
     });
 
     let msg = create_poll_msg("test", "test", None, Some(execute_msgs), None);
@@ -3420,7 +4118,7 @@ fn happy_days_end_poll_with_controlled_quorum() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -3442,7 +4140,7 @@ fn happy_days_end_poll_with_controlled_quorum() {
             amount: Uint128::from(stake_amount),
         },
     };
-    let env = mock_env_height(0, POLL_START_TIME);
+    let env = mock_env_height(POLL_START_HEIGHT, POLL_START_TIME);
     let info = mock_info(TEST_VOTER, &[]);
     let execute_res = execute(deps.as_mut(), env, info, msg).unwrap();
 
@@ -3495,7 +4193,7 @@ fn happy_days_end_poll_with_controlled_quorum() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER_2.to_string(),
         amount: Uint128::from(8 * stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let env = mock_env();
@@ -3585,7 +4283,6 @@ fn happy_days_end_poll_with_controlled_quorum() {
 
 #[test]
 fn execute_poll_with_empty_execute_data() {
-    const POLL_START_HEIGHT: u64 = 1000;
     const POLL_ID: u64 = 1;
     let stake_amount = 1000;
 
@@ -3627,7 +4324,7 @@ fn execute_poll_with_empty_execute_data() {
     let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: TEST_VOTER.to_string(),
         amount: Uint128::from(stake_amount as u128),
-        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {}).unwrap(),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
     });
 
     let info = mock_info(VOTING_TOKEN, &[]);
@@ -3714,9 +4411,7 @@ fn execute_poll_with_empty_execute_data() {
     };
     let execute_err = execute(deps.as_mut(), creator_env.clone(), creator_info, msg).unwrap_err();
     match execute_err {
-        StdError::GenericErr { msg, .. } => {
-            assert_eq!(msg, "The poll does not have executable data")
-        }
+        ContractError::NoExecutableData => {}
         _ => panic!("DO NOT ENTER HERE"),
     }
 
@@ -3738,3 +4433,3999 @@ fn execute_poll_with_empty_execute_data() {
     let polls_res: PollsResponse = from_binary(&res).unwrap();
     assert_eq!(polls_res.polls[0], poll_res);
 }
+
+fn stake_voting_tokens(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    staker: &str,
+    amount: u128,
+    contract_balance: u128,
+) {
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::new(contract_balance),
+        )],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: staker.to_string(),
+        amount: Uint128::new(amount),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+}
+
+fn cast_vote(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    voter: &str,
+    vote: VoteOption,
+    amount: u128,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CastVote {
+            poll_id: 1,
+            vote,
+            amount: Uint128::new(amount),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(voter, &[]), msg)
+}
+
+// creates poll and stakes 100 tokens for TEST_VOTER and 50 tokens for TEST_VOTER_2,
+// TEST_VOTER_2 delegates to TEST_VOTER
+fn mock_delegation(deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>) {
+    mock_init(deps);
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    stake_voting_tokens(deps, TEST_VOTER, 100, 100 + DEFAULT_PROPOSAL_DEPOSIT);
+    stake_voting_tokens(deps, TEST_VOTER_2, 50, 150 + DEFAULT_PROPOSAL_DEPOSIT);
+
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::Delegate {
+            delegate: TEST_VOTER.to_string(),
+        },
+    };
+    let execute_res =
+        execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "delegate"),
+            attr("delegator", TEST_VOTER_2),
+            attr("delegate", TEST_VOTER),
+            attr("share", "50"),
+        ]
+    );
+}
+
+fn query_poll_votes(deps: &OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>) -> (u128, u128) {
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll: PollResponse = from_binary(&res).unwrap();
+    (poll.yes_votes.u128(), poll.no_votes.u128())
+}
+
+#[test]
+fn happy_days_delegate() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Delegation {
+            address: TEST_VOTER_2.to_string(),
+        },
+    )
+    .unwrap();
+    let response: DelegationResponse = from_binary(&res).unwrap();
+    assert_eq!(response.delegate, Some(TEST_VOTER.to_string()));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Delegators {
+            delegate: TEST_VOTER.to_string(),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: DelegatorsResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.delegators,
+        vec![DelegatorsResponseItem {
+            delegator: TEST_VOTER_2.to_string(),
+            share: Uint128::new(50),
+            balance: Uint128::new(50),
+        }]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Delegate {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: DelegateResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response,
+        DelegateResponse {
+            delegated_share: Uint128::new(50),
+            delegated_balance: Uint128::new(50),
+            voting_power: Uint128::new(150),
+        }
+    );
+
+    // delegate can't vote with more than own and delegated tokens
+    let res = cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 151);
+    match res {
+        Err(ContractError::InsufficientStake) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let execute_res = cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 150).unwrap();
+    assert_cast_vote_success(TEST_VOTER, 150, 1, VoteOption::Yes, execute_res);
+    assert_eq!(query_poll_votes(&deps), (150, 0));
+
+    // only own tokens are locked
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.locked_balance,
+        vec![(
+            1u64,
+            VoterInfo {
+                vote: VoteOption::Yes,
+                balance: Uint128::new(100),
+            }
+        )]
+    );
+}
+
+#[test]
+fn delegator_overrides_delegate_vote() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 150).unwrap();
+    cast_vote(&mut deps, TEST_VOTER_2, VoteOption::No, 50).unwrap();
+    assert_eq!(query_poll_votes(&deps), (100, 50));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Voters {
+            poll_id: 1,
+            start_after: None,
+            limit: None,
+            order_by: Some(OrderBy::Asc),
+        },
+    )
+    .unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.voters,
+        vec![
+            VotersResponseItem {
+                voter: TEST_VOTER.to_string(),
+                vote: VoteOption::Yes,
+                balance: Uint128::new(100),
+            },
+            VotersResponseItem {
+                voter: TEST_VOTER_2.to_string(),
+                vote: VoteOption::No,
+                balance: Uint128::new(50),
+            },
+        ]
+    );
+}
+
+#[test]
+fn delegate_cannot_use_tokens_of_voted_delegator() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    cast_vote(&mut deps, TEST_VOTER_2, VoteOption::No, 50).unwrap();
+    let res = cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 101);
+    match res {
+        Err(ContractError::InsufficientStake) => {}
+        _ => panic!("Must return error"),
+    }
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 100).unwrap();
+    assert_eq!(query_poll_votes(&deps), (100, 50));
+}
+
+#[test]
+fn undelegate_keeps_delegate_votes_in_created_polls() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 150).unwrap();
+
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::Undelegate {},
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(TEST_VOTER_2, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "undelegate"),
+            attr("delegator", TEST_VOTER_2),
+            attr("delegate", TEST_VOTER),
+            attr("share", "50"),
+        ]
+    );
+    // voting power in the poll is taken from the checkpoint before its creation
+    assert_eq!(query_poll_votes(&deps), (150, 0));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Delegate {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: DelegateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.voting_power, Uint128::new(100));
+
+    // delegator still can override the delegate vote
+    cast_vote(&mut deps, TEST_VOTER_2, VoteOption::No, 50).unwrap();
+    assert_eq!(query_poll_votes(&deps), (100, 50));
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg);
+    match res {
+        Err(ContractError::NothingDelegated) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn withdraw_keeps_delegate_votes_in_created_polls() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 150).unwrap();
+
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::WithdrawVotingTokens {
+            amount: Some(Uint128::new(20)),
+        },
+    };
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(TEST_VOTER_2, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(query_poll_votes(&deps), (150, 0));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Delegate {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: DelegateResponse = from_binary(&res).unwrap();
+    assert_eq!(response.delegated_share, Uint128::new(30));
+}
+
+#[test]
+fn fails_delegate() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    let delegate_msg = |delegate: &str| ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::Delegate {
+            delegate: delegate.to_string(),
+        },
+    };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        delegate_msg(TEST_VOTER),
+    );
+    match res {
+        Err(ContractError::SelfDelegation) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_3, &[]),
+        delegate_msg(TEST_VOTER),
+    );
+    match res {
+        Err(ContractError::NothingStaked) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_2, &[]),
+        delegate_msg(TEST_VOTER),
+    );
+    match res {
+        Err(ContractError::AlreadyDelegated) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+fn change_vote_msg(vote: VoteOption, amount: u128) -> ExecuteMsg {
+    ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ChangeVote {
+            poll_id: 1,
+            vote,
+            amount: Uint128::new(amount),
+        },
+    }
+}
+
+fn retract_vote_msg() -> ExecuteMsg {
+    ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::RetractVote { poll_id: 1 },
+    }
+}
+
+fn query_locked_balance(
+    deps: &OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    address: &str,
+) -> Vec<(u64, VoterInfo)> {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: address.to_string(),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    response.locked_balance
+}
+
+#[test]
+fn happy_days_change_vote() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100 + DEFAULT_PROPOSAL_DEPOSIT);
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 60).unwrap();
+    assert_eq!(query_poll_votes(&deps), (60, 0));
+
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        change_vote_msg(VoteOption::No, 100),
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "change_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "100"),
+            attr("voter", TEST_VOTER),
+            attr("vote_option", "no"),
+            attr("previous_amount", "60"),
+            attr("previous_vote_option", "yes"),
+        ]
+    );
+    assert_eq!(query_poll_votes(&deps), (0, 100));
+    assert_eq!(
+        query_locked_balance(&deps, TEST_VOTER),
+        vec![(
+            1u64,
+            VoterInfo {
+                vote: VoteOption::No,
+                balance: Uint128::new(100),
+            }
+        )]
+    );
+    assert_eq!(
+        load_poll_voter(deps.as_ref().storage, 1, &Addr::unchecked(TEST_VOTER)).unwrap(),
+        VoterInfo {
+            vote: VoteOption::No,
+            balance: Uint128::new(100),
+        }
+    );
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        change_vote_msg(VoteOption::Yes, 101),
+    );
+    match res {
+        Err(ContractError::InsufficientStake) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn happy_days_retract_vote() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100 + DEFAULT_PROPOSAL_DEPOSIT);
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 60).unwrap();
+
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        retract_vote_msg(),
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "retract_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "60"),
+            attr("voter", TEST_VOTER),
+            attr("vote_option", "yes"),
+        ]
+    );
+    assert_eq!(query_poll_votes(&deps), (0, 0));
+    assert_eq!(query_locked_balance(&deps, TEST_VOTER), vec![]);
+    assert!(load_poll_voter(deps.as_ref().storage, 1, &Addr::unchecked(TEST_VOTER)).is_err());
+
+    // nothing to retract anymore
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        retract_vote_msg(),
+    );
+    match res {
+        Err(ContractError::NotVoted) => {}
+        _ => panic!("Must return error"),
+    }
+
+    // tokens are unlocked
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::WithdrawVotingTokens {
+            amount: Some(Uint128::new(100)),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+}
+
+#[test]
+fn fails_change_vote_after_voting_period() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100 + DEFAULT_PROPOSAL_DEPOSIT);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        change_vote_msg(VoteOption::No, 100),
+    );
+    match res {
+        Err(ContractError::NotVoted) => {}
+        _ => panic!("Must return error"),
+    }
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 60).unwrap();
+
+    let mut env = mock_env();
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD + 1);
+    for msg in [change_vote_msg(VoteOption::No, 100), retract_vote_msg()] {
+        let res = execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg);
+        match res {
+            Err(ContractError::PollNotInProgress) => {}
+            _ => panic!("Must return error"),
+        }
+    }
+}
+
+#[test]
+fn change_and_retract_vote_with_snapshot() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100 + DEFAULT_PROPOSAL_DEPOSIT);
+    stake_voting_tokens(&mut deps, TEST_VOTER_2, 100, 200 + DEFAULT_PROPOSAL_DEPOSIT);
+
+    // votes before snapshot period don't take a snapshot
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 100).unwrap();
+    cast_vote(&mut deps, TEST_VOTER_2, VoteOption::Yes, 100).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(None, value.staked_amount);
+
+    // change vote in snapshot period takes a snapshot
+    let mut env = mock_env();
+    env.block.time = env
+        .block
+        .time
+        .plus_seconds(DEFAULT_VOTING_PERIOD - DEFAULT_FIX_PERIOD + 1);
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        change_vote_msg(VoteOption::No, 100),
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(Uint128::new(200)), value.staked_amount);
+
+    // snapshot is not overwritten by the following changes
+    stake_voting_tokens(
+        &mut deps,
+        TEST_VOTER_3,
+        1000,
+        1200 + DEFAULT_PROPOSAL_DEPOSIT,
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
+        retract_vote_msg(),
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(Uint128::new(200)), value.staked_amount);
+    assert_eq!((value.yes_votes.u128(), value.no_votes.u128()), (0, 100));
+
+    // quorum is calculated with the snapshot
+    env.block.time = env.block.time.plus_seconds(DEFAULT_FIX_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "Threshold not reached"),
+            attr("passed", "false"),
+        ]
+    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let value: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(Some(Uint128::new(200)), value.total_balance_at_end_poll);
+}
+
+#[test]
+fn retract_vote_keeps_delegate_override() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 150).unwrap();
+    cast_vote(&mut deps, TEST_VOTER_2, VoteOption::No, 50).unwrap();
+    assert_eq!(query_poll_votes(&deps), (100, 50));
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_2, &[]),
+        retract_vote_msg(),
+    )
+    .unwrap();
+    assert_eq!(query_poll_votes(&deps), (100, 0));
+
+    // delegate can't use retracted tokens in this poll
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        change_vote_msg(VoteOption::Yes, 150),
+    );
+    match res {
+        Err(ContractError::InsufficientStake) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn cast_vote_is_capped_at_poll_creation_checkpoint() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // tokens staked in the poll creation block are not counted
+    for (staker, contract_balance) in [(TEST_VOTER, 200), (TEST_VOTER_2, 300)] {
+        deps.querier.with_token_balances(&[(
+            &VOTING_TOKEN.to_string(),
+            &[(
+                &MOCK_CONTRACT_ADDR.to_string(),
+                &Uint128::new(contract_balance + DEFAULT_PROPOSAL_DEPOSIT),
+            )],
+        )]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: staker.to_string(),
+            amount: Uint128::new(100),
+            msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
+        });
+        execute(
+            deps.as_mut(),
+            mock_env_poll_start(),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    for (voter, amount) in [(TEST_VOTER, 101), (TEST_VOTER_2, 1)] {
+        let res = cast_vote(&mut deps, voter, VoteOption::Yes, amount);
+        match res {
+            Err(ContractError::InsufficientStake) => {}
+            _ => panic!("Must return error"),
+        }
+    }
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 100).unwrap();
+    assert_eq!(query_poll_votes(&deps), (100, 0));
+
+    // the next poll takes into account all tokens staked before its creation
+    let msg = create_poll_msg("test2", "test2", None, None, None);
+    let mut env = mock_env_height(POLL_START_HEIGHT + 1, mock_env().block.time.seconds());
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::new(300 + 2 * DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD - 1);
+    let res = execute(
+        deps.as_mut(),
+        env,
+        mock_info(TEST_VOTER_2, &[]),
+        ExecuteMsg::Anyone {
+            anyone_msg: AnyoneMsg::SnapshotPoll { poll_id: 2 },
+        },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            attr("action", "snapshot_poll"),
+            attr("poll_id", "2"),
+            attr("staked_amount", "300"),
+        ]
+    );
+
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CastVote {
+            poll_id: 2,
+            vote: VoteOption::No,
+            amount: Uint128::new(100),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+}
+
+fn set_unbonding_period(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    unbonding_period: u64,
+) {
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::UpdateConfig {
+            quorum: None,
+            threshold: None,
+            voting_period: None,
+            timelock_period: None,
+            proposal_deposit: None,
+            snapshot_period: None,
+            unbonding_period: Some(unbonding_period),
+            expiration_period: None,
+            max_execution_retries: None,
+            keeper_reward: None,
+            max_keeper_reward_per_poll: None,
+            forfeited_deposit_policy: None,
+            rejected_deposit_refund: None,
+            category_params: None,
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+}
+
+fn add_rewards(deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>, amount: u128) {
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::AddRewards {
+            amount: Uint128::new(amount),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+}
+
+fn query_unbonding(
+    deps: &OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    address: &str,
+) -> Vec<UnbondingEntry> {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Unbonding {
+            address: address.to_string(),
+        },
+    )
+    .unwrap();
+    let response: UnbondingResponse = from_binary(&res).unwrap();
+    response.entries
+}
+
+#[test]
+fn happy_days_withdraw_with_unbonding_period() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    set_unbonding_period(&mut deps, 100);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+
+    let env = mock_env();
+    let release_time = env.block.time.seconds() + 100;
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::WithdrawVotingTokens {
+            amount: Some(Uint128::new(40)),
+        },
+    };
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert!(execute_res.messages.is_empty());
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "withdraw"),
+            attr("recipient", TEST_VOTER),
+            attr("amount", "40"),
+            attr("release_time", release_time.to_string()),
+        ]
+    );
+    assert_eq!(
+        query_unbonding(&deps, TEST_VOTER),
+        vec![UnbondingEntry {
+            amount: Uint128::new(40),
+            release_time,
+        }]
+    );
+
+    let state: State = load_state(deps.as_ref().storage).unwrap();
+    assert_eq!(
+        state,
+        State {
+            poll_count: 0,
+            total_share: Uint128::new(60),
+            total_staked: Uint128::new(60),
+            total_deposit: Uint128::zero(),
+            total_unbonding: Uint128::new(40),
+            keeper_reward_pool: Uint128::zero(),
+        }
+    );
+
+    // unbonding tokens are still on the contract, but excluded from share calculation
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_VOTER_2.to_string(),
+        amount: Uint128::new(60),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens { recipient: None }).unwrap(),
+    });
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(160))],
+    )]);
+    let execute_res =
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    assert_eq!(execute_res.attributes[2], attr("share", "60"));
+    let state: State = load_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_share, Uint128::new(120));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.balance, Uint128::new(60));
+
+    // tokens are transferred only after the unbonding period
+    let claim_msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ClaimUnbonded {},
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        claim_msg.clone(),
+    );
+    match res {
+        Err(ContractError::NothingToClaim) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let env = mock_env_height(POLL_START_HEIGHT, release_time);
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), claim_msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_VOTER.to_string(),
+                amount: Uint128::new(40),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "claim_unbonded"),
+            attr("recipient", TEST_VOTER),
+            attr("amount", "40"),
+        ]
+    );
+    assert_eq!(query_unbonding(&deps, TEST_VOTER), vec![]);
+
+    let state: State = load_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_unbonding, Uint128::zero());
+}
+
+#[test]
+fn claim_unbonded_pays_only_released_entries() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    set_unbonding_period(&mut deps, 100);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+
+    let withdraw_msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::WithdrawVotingTokens {
+            amount: Some(Uint128::new(10)),
+        },
+    };
+    let start_time = mock_env().block.time.seconds();
+    for time in [start_time, start_time + 50] {
+        let env = mock_env_height(POLL_START_HEIGHT, time);
+        execute(
+            deps.as_mut(),
+            env,
+            mock_info(TEST_VOTER, &[]),
+            withdraw_msg.clone(),
+        )
+        .unwrap();
+    }
+
+    let env = mock_env_height(POLL_START_HEIGHT, start_time + 120);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ClaimUnbonded {},
+    };
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_eq!(execute_res.attributes[2], attr("amount", "10"));
+    assert_eq!(
+        query_unbonding(&deps, TEST_VOTER),
+        vec![UnbondingEntry {
+            amount: Uint128::new(10),
+            release_time: start_time + 150,
+        }]
+    );
+
+    let state: State = load_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_unbonding, Uint128::new(10));
+    assert_eq!(state.total_share, Uint128::new(80));
+}
+
+#[test]
+fn stray_transfer_does_not_change_staker_balance() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+
+    // somebody transfers tokens to the contract without staking them
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(300))],
+    )]);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.balance, Uint128::new(100));
+
+    stake_voting_tokens(&mut deps, TEST_VOTER_2, 100, 400);
+    let state: State = load_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_share, Uint128::new(200));
+    assert_eq!(state.total_staked, Uint128::new(200));
+}
+
+#[test]
+fn happy_days_add_rewards() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(150))],
+    )]);
+
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::AddRewards {
+            amount: Uint128::new(50),
+        },
+    };
+    let execute_res =
+        execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "add_rewards"),
+            attr("amount", "50"),
+            attr("total_staked", "150"),
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let response: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.balance, Uint128::new(150));
+    assert_eq!(response.share, Uint128::new(100));
+}
+
+#[test]
+fn fails_add_rewards() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::AddRewards {
+            amount: Uint128::new(50),
+        },
+    };
+
+    // nobody staked yet
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::NothingStaked) => {}
+        _ => panic!("Must return error"),
+    }
+
+    // deposit is not free to be added
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        create_poll_msg("test", "test", None, None, None),
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::new(140 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::InsufficientFreeBalance(amount)) => {
+            assert_eq!(amount, Uint128::new(40))
+        }
+        _ => panic!("Must return error"),
+    }
+
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::Unauthorized) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn happy_days_cancel_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CancelPoll { poll_id: 1 },
+    };
+    let execute_res =
+        execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: VOTING_TOKEN.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cancel_poll"),
+            attr("poll_id", "1"),
+            attr("refund", DEFAULT_PROPOSAL_DEPOSIT.to_string()),
+        ]
+    );
+
+    let state: State = load_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Cancelled),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 1);
+    assert_eq!(response.polls[0].status, PollStatus::Cancelled);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::InProgress),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 0);
+}
+
+#[test]
+fn fails_cancel_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CancelPoll { poll_id: 1 },
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized) => {}
+        _ => panic!("Must return error"),
+    }
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::No, 10).unwrap();
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg);
+    match res {
+        Err(ContractError::PollHasVotes) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn fails_cancel_poll_after_voting_period() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let mut env = mock_env_poll_start();
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD + 1);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CancelPoll { poll_id: 1 },
+    };
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    );
+    match res {
+        Err(ContractError::VotingPeriodExpired) => {}
+        _ => panic!("Must return error"),
+    }
+
+    // the poll can only be ended, without quorum the deposit is not refunded
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(deps.as_mut(), env, mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(execute_res.messages, vec![]);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Rejected);
+}
+
+fn set_guardian(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    guardian: Option<&str>,
+) {
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::UpdateGuardian {
+            guardian: guardian.map(|addr| addr.to_string()),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+}
+
+fn veto_poll_msg() -> ExecuteMsg {
+    ExecuteMsg::Guardian {
+        guardian_msg: GuardianMsg::VetoPoll { poll_id: 1 },
+    }
+}
+
+#[test]
+fn update_guardian() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::UpdateGuardian {
+            guardian: Some(TEST_GUARDIAN.to_string()),
+        },
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_GUARDIAN, &[]),
+        msg.clone(),
+    );
+    match res {
+        Err(ContractError::Unauthorized) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let execute_res =
+        execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "update_guardian"),
+            attr("guardian", TEST_GUARDIAN),
+        ]
+    );
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.guardian, Some(TEST_GUARDIAN.to_string()));
+
+    set_guardian(&mut deps, None);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(config.guardian, None);
+}
+
+#[test]
+fn happy_days_veto_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    set_guardian(&mut deps, Some(TEST_GUARDIAN));
+    let mut env = pass_poll_with_message(&mut deps);
+
+    let execute_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_GUARDIAN, &[]),
+        veto_poll_msg(),
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![attr("action", "veto_poll"), attr("poll_id", "1")]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Vetoed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 1);
+    assert_eq!(response.polls[0].status, PollStatus::Vetoed);
+
+    // vetoed poll can not be executed
+    env.block.time = env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ExecutePoll { poll_id: 1 },
+    };
+    let res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg);
+    match res {
+        Err(ContractError::PollNotPassed) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn veto_failed_poll_with_retries_left() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    set_guardian(&mut deps, Some(TEST_GUARDIAN));
+    let mut env = pass_poll_with_message(&mut deps);
+    env.block.time = env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
+    let execute_msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ExecutePoll { poll_id: 1 },
+    };
+    let fail_reply = Reply {
+        id: POLL_EXECUTE_REPLY_ID,
+        result: ContractResult::Err("Error".to_string()),
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        execute_msg.clone(),
+    )
+    .unwrap();
+    reply(deps.as_mut(), mock_env(), fail_reply).unwrap();
+
+    // no retries are allowed by default
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_GUARDIAN, &[]),
+        veto_poll_msg(),
+    );
+    match res {
+        Err(ContractError::PollNotPassed) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let mut config = load_config(&deps.storage).unwrap();
+    config.max_execution_retries = 1;
+    store_config(&mut deps.storage, &config).unwrap();
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_GUARDIAN, &[]),
+        veto_poll_msg(),
+    )
+    .unwrap();
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Polls {
+            filter: Some(PollStatus::Failed),
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: PollsResponse = from_binary(&res).unwrap();
+    assert_eq!(response.polls.len(), 0);
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Vetoed);
+
+    // vetoed poll can not be retried
+    let res = execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), execute_msg);
+    match res {
+        Err(ContractError::PollNotPassed) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn fails_veto_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // no guardian is set
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_GUARDIAN, &[]),
+        veto_poll_msg(),
+    );
+    match res {
+        Err(ContractError::Unauthorized) => {}
+        _ => panic!("Must return error"),
+    }
+
+    // only guardian can veto
+    set_guardian(&mut deps, Some(TEST_GUARDIAN));
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_CREATOR, &[]),
+        veto_poll_msg(),
+    );
+    match res {
+        Err(ContractError::Unauthorized) => {}
+        _ => panic!("Must return error"),
+    }
+
+    // poll in progress can not be vetoed
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_GUARDIAN, &[]),
+        veto_poll_msg(),
+    );
+    match res {
+        Err(ContractError::PollNotPassed) => {}
+        _ => panic!("Must return error"),
+    }
+
+    // guardian has no governance rights
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::UpdateGuardian {
+            guardian: Some(TEST_VOTER.to_string()),
+        },
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_GUARDIAN, &[]),
+        msg,
+    );
+    match res {
+        Err(ContractError::Unauthorized) => {}
+        _ => panic!("Must return error"),
+    }
+
+    // removed guardian can not veto anymore
+    set_guardian(&mut deps, None);
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_GUARDIAN, &[]),
+        veto_poll_msg(),
+    );
+    match res {
+        Err(ContractError::Unauthorized) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+fn create_categorized_poll(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    execute_msgs: Option<Vec<PollExecuteMsg>>,
+    migrate_msgs: Option<Vec<PollMigrateMsg>>,
+    category: Option<PollCategory>,
+    deposit: u128,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::new(deposit),
+        msg: to_binary(&Cw20HookMsg::CreatePoll {
+            title: "test".to_string(),
+            description: "test".to_string(),
+            link: None,
+            execute_msgs,
+            migrate_msgs,
+            execution_mode: None,
+            category,
+            options: None,
+            winning_rule: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+}
+
+fn set_category_params(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    category_params: Vec<PollCategoryParams>,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::UpdateConfig {
+            quorum: None,
+            threshold: None,
+            voting_period: None,
+            timelock_period: None,
+            proposal_deposit: None,
+            snapshot_period: None,
+            unbonding_period: None,
+            expiration_period: None,
+            max_execution_retries: None,
+            keeper_reward: None,
+            max_keeper_reward_per_poll: None,
+            forfeited_deposit_policy: None,
+            rejected_deposit_refund: None,
+            category_params: Some(category_params),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg)
+}
+
+#[test]
+fn poll_category_is_inferred_from_messages() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+
+    let execute_msg = |msg: Binary| {
+        Some(vec![PollExecuteMsg {
+            order: 1,
+            contract: VOTING_TOKEN.to_string(),
+            msg: Some(msg),
+            action: None,
+            funds: vec![],
+        }])
+    };
+    let burn_msg = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(1),
+    })
+    .unwrap();
+    let spend_msg = to_binary(&services::community::ExecuteMsg::Governance {
+        governance_msg: services::community::GovernanceMsg::Spend {
+            recipient: TEST_VOTER.to_string(),
+            amount: Uint128::new(1),
+        },
+    })
+    .unwrap();
+    let update_params_msg = to_binary(&ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::UpdateConfig {
+            quorum: None,
+            threshold: None,
+            voting_period: None,
+            timelock_period: None,
+            proposal_deposit: None,
+            snapshot_period: None,
+            unbonding_period: None,
+            expiration_period: None,
+            max_execution_retries: None,
+            keeper_reward: None,
+            max_keeper_reward_per_poll: None,
+            forfeited_deposit_policy: None,
+            rejected_deposit_refund: None,
+            category_params: Some(vec![]),
+        },
+    })
+    .unwrap();
+    let marketing_msg = to_binary(&Cw20ExecuteMsg::UpdateMarketing {
+        project: None,
+        description: Some("psi".to_string()),
+        marketing: None,
+    })
+    .unwrap();
+    let ownership_msg = to_binary(&services::staking::ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::ProposeNewOwner {
+            owner: TEST_VOTER.to_string(),
+            expires_in: None,
+        },
+    })
+    .unwrap();
+    let community_owner_msg = to_binary(&services::community::ExecuteMsg::Governance {
+        governance_msg: services::community::GovernanceMsg::UpdateConfig {
+            governance_contract_addr: Some(TEST_VOTER.to_string()),
+        },
+    })
+    .unwrap();
+    let migrate_msgs = Some(vec![PollMigrateMsg {
+        order: 1,
+        contract: VOTING_TOKEN.to_string(),
+        msg: burn_msg.clone(),
+        new_code_id: 2,
+    }]);
+
+    let polls = vec![
+        (None, None, None, PollCategory::Text),
+        (
+            execute_msg(marketing_msg.clone()),
+            None,
+            None,
+            PollCategory::ParameterChange,
+        ),
+        (
+            execute_msg(spend_msg),
+            None,
+            None,
+            PollCategory::TreasurySpend,
+        ),
+        // treasury tokens are destroyed or can be moved by others
+        (
+            execute_msg(burn_msg.clone()),
+            None,
+            None,
+            PollCategory::TreasurySpend,
+        ),
+        (
+            execute_msg(
+                to_binary(&Cw20ExecuteMsg::BurnFrom {
+                    owner: TEST_VOTER.to_string(),
+                    amount: Uint128::new(1),
+                })
+                .unwrap(),
+            ),
+            None,
+            None,
+            PollCategory::TreasurySpend,
+        ),
+        (
+            execute_msg(
+                to_binary(&Cw20ExecuteMsg::IncreaseAllowance {
+                    spender: TEST_VOTER.to_string(),
+                    amount: Uint128::new(1),
+                    expires: None,
+                })
+                .unwrap(),
+            ),
+            None,
+            None,
+            PollCategory::TreasurySpend,
+        ),
+        (
+            execute_msg(
+                to_binary(&Cw20ExecuteMsg::TransferFrom {
+                    owner: TEST_VOTER.to_string(),
+                    recipient: TEST_VOTER_2.to_string(),
+                    amount: Uint128::new(1),
+                })
+                .unwrap(),
+            ),
+            None,
+            None,
+            PollCategory::TreasurySpend,
+        ),
+        (
+            execute_msg(
+                to_binary(&Cw20ExecuteMsg::SendFrom {
+                    owner: TEST_VOTER.to_string(),
+                    contract: TEST_VOTER_2.to_string(),
+                    amount: Uint128::new(1),
+                    msg: Binary::default(),
+                })
+                .unwrap(),
+            ),
+            None,
+            None,
+            PollCategory::TreasurySpend,
+        ),
+        // ownership of any protocol contract hands over its funds
+        (
+            Some(vec![PollExecuteMsg {
+                contract: "staking".to_string(),
+                ..execute_msg(ownership_msg).unwrap().remove(0)
+            }]),
+            None,
+            None,
+            PollCategory::CodeMigration,
+        ),
+        (
+            Some(vec![PollExecuteMsg {
+                contract: "community".to_string(),
+                ..execute_msg(community_owner_msg).unwrap().remove(0)
+            }]),
+            None,
+            None,
+            PollCategory::CodeMigration,
+        ),
+        (None, migrate_msgs, None, PollCategory::CodeMigration),
+        (
+            Some(vec![action_msg(
+                1,
+                "staking",
+                PollAction::AddStakingSchedules { schedules: vec![] },
+            )]),
+            None,
+            None,
+            PollCategory::TreasurySpend,
+        ),
+        (
+            Some(vec![PollExecuteMsg {
+                contract: MOCK_CONTRACT_ADDR.to_string(),
+                ..execute_msg(update_params_msg.clone()).unwrap().remove(0)
+            }]),
+            None,
+            None,
+            PollCategory::CodeMigration,
+        ),
+        // the same message to another contract does not change governance
+        (
+            execute_msg(update_params_msg.clone()),
+            None,
+            None,
+            PollCategory::ParameterChange,
+        ),
+        // stricter category can be declared for parameter change
+        (
+            execute_msg(marketing_msg.clone()),
+            None,
+            Some(PollCategory::TreasurySpend),
+            PollCategory::TreasurySpend,
+        ),
+    ];
+    for (poll_id, (execute_msgs, migrate_msgs, declared, expected)) in polls.into_iter().enumerate()
+    {
+        create_categorized_poll(
+            &mut deps,
+            execute_msgs,
+            migrate_msgs,
+            declared,
+            DEFAULT_PROPOSAL_DEPOSIT,
+        )
+        .unwrap();
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Poll {
+                poll_id: poll_id as u64 + 1,
+            },
+        )
+        .unwrap();
+        let poll_res: PollResponse = from_binary(&res).unwrap();
+        assert_eq!(poll_res.category, expected);
+    }
+
+    let res = create_categorized_poll(
+        &mut deps,
+        execute_msg(burn_msg),
+        None,
+        Some(PollCategory::Text),
+        DEFAULT_PROPOSAL_DEPOSIT,
+    );
+    match res {
+        Err(ContractError::PollCategoryMismatch) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn poll_uses_category_params() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+
+    let text_params = PollParams {
+        quorum: Decimal::percent(90),
+        threshold: Decimal::percent(50),
+        voting_period: 100,
+        timelock_period: 0,
+        proposal_deposit: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT * 2),
+    };
+    set_category_params(
+        &mut deps,
+        vec![PollCategoryParams {
+            category: PollCategory::Text,
+            params: text_params.clone(),
+        }],
+    )
+    .unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config.category_params,
+        vec![PollCategoryParams {
+            category: PollCategory::Text,
+            params: text_params,
+        }]
+    );
+
+    let res = create_categorized_poll(&mut deps, None, None, None, DEFAULT_PROPOSAL_DEPOSIT);
+    match res {
+        Err(ContractError::InsufficientDeposit(amount)) => {
+            assert_eq!(amount, Uint128::new(DEFAULT_PROPOSAL_DEPOSIT * 2))
+        }
+        _ => panic!("Must return error"),
+    }
+
+    let execute_res =
+        create_categorized_poll(&mut deps, None, None, None, DEFAULT_PROPOSAL_DEPOSIT * 2).unwrap();
+    let end_time = mock_env().block.time.seconds() + 100;
+    assert_eq!(
+        execute_res.attributes[3],
+        attr("end_time", end_time.to_string())
+    );
+
+    // 50% of staked tokens is enough for default quorum but not for text polls
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 500).unwrap();
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env_height(POLL_START_HEIGHT, end_time),
+        mock_info(TEST_VOTER, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes[2],
+        attr("rejected_reason", "Quorum not reached")
+    );
+}
+
+#[test]
+fn fails_update_category_params() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+
+    let params = PollParams {
+        quorum: Decimal::percent(10),
+        threshold: Decimal::percent(50),
+        voting_period: 100,
+        timelock_period: 0,
+        proposal_deposit: Uint128::new(1),
+    };
+    let res = set_category_params(
+        &mut deps,
+        vec![
+            PollCategoryParams {
+                category: PollCategory::Text,
+                params: params.clone(),
+            },
+            PollCategoryParams {
+                category: PollCategory::Text,
+                params: params.clone(),
+            },
+        ],
+    );
+    match res {
+        Err(ContractError::DuplicatedPollCategory) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let res = set_category_params(
+        &mut deps,
+        vec![PollCategoryParams {
+            category: PollCategory::CodeMigration,
+            params: PollParams {
+                quorum: Decimal::percent(101),
+                ..params
+            },
+        }],
+    );
+    match res {
+        Err(ContractError::InvalidQuorum) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+fn burn_msg(amount: u128) -> PollExecuteMsg {
+    PollExecuteMsg {
+        order: 1,
+        contract: VOTING_TOKEN.to_string(),
+        msg: Some(
+            to_binary(&Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(amount),
+            })
+            .unwrap(),
+        ),
+        action: None,
+        funds: vec![],
+    }
+}
+
+fn create_multi_choice_poll(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    execute_msgs: Option<Vec<PollExecuteMsg>>,
+    options: Vec<PollOption>,
+    winning_rule: Option<WinningRule>,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+        msg: to_binary(&Cw20HookMsg::CreatePoll {
+            title: "test".to_string(),
+            description: "test".to_string(),
+            link: None,
+            execute_msgs,
+            migrate_msgs: None,
+            execution_mode: None,
+            category: None,
+            options: Some(options),
+            winning_rule,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+}
+
+fn three_poll_options() -> Vec<PollOption> {
+    vec![
+        PollOption {
+            name: "first".to_string(),
+            execute_msgs: Some(vec![burn_msg(1)]),
+        },
+        PollOption {
+            name: "second".to_string(),
+            execute_msgs: Some(vec![burn_msg(2)]),
+        },
+        PollOption {
+            name: "third".to_string(),
+            execute_msgs: None,
+        },
+    ]
+}
+
+#[test]
+fn multi_choice_poll_executes_winning_option() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 600, 600);
+    stake_voting_tokens(&mut deps, TEST_VOTER_2, 400, 1000);
+    create_multi_choice_poll(&mut deps, None, three_poll_options(), None).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.category, PollCategory::TreasurySpend);
+    assert_eq!(poll_res.winning_rule, WinningRule::Plurality);
+
+    let execute_res =
+        cast_vote(&mut deps, TEST_VOTER, VoteOption::Choice { option: 1 }, 600).unwrap();
+    assert_eq!(execute_res.attributes[4], attr("vote_option", "option_1"));
+    cast_vote(
+        &mut deps,
+        TEST_VOTER_2,
+        VoteOption::Choice { option: 0 },
+        400,
+    )
+    .unwrap();
+
+    let mut env = mock_env_poll_start();
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(execute_res.attributes[3], attr("passed", "true"));
+    assert_eq!(execute_res.attributes[4], attr("winning_option", "1"));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Passed);
+    assert_eq!(poll_res.winning_option, Some(1));
+    assert_eq!(
+        poll_res
+            .options
+            .iter()
+            .map(|option| option.votes)
+            .collect::<Vec<Uint128>>(),
+        vec![Uint128::new(400), Uint128::new(600), Uint128::zero()]
+    );
+    assert_eq!(poll_res.options[1].execute_msgs, Some(vec![burn_msg(2)]));
+
+    env.block.time = env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ExecutePoll { poll_id: 1 },
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // only messages of the winning option are executed
+    let msg = ExecuteMsg::Yourself {
+        yourself_msg: YourselfMsg::ExecutePollMsgs { poll_id: 1 },
+    };
+    let execute_res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.messages,
+        vec![SubMsg::reply_on_success(
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: VOTING_TOKEN.to_string(),
+                msg: burn_msg(2).msg.unwrap(),
+                funds: vec![],
+            }),
+            100
+        )]
+    );
+}
+
+#[test]
+fn multi_choice_poll_without_winner_is_rejected() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 600, 600);
+    stake_voting_tokens(&mut deps, TEST_VOTER_2, 400, 1000);
+    create_multi_choice_poll(
+        &mut deps,
+        None,
+        three_poll_options(),
+        Some(WinningRule::MinimumShare {
+            share: Decimal::percent(70),
+        }),
+    )
+    .unwrap();
+
+    // abstain votes count for quorum but not for the option share
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Choice { option: 2 }, 600).unwrap();
+    cast_vote(&mut deps, TEST_VOTER_2, VoteOption::Abstain, 400).unwrap();
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ChangeVote {
+            poll_id: 1,
+            vote: VoteOption::Choice { option: 0 },
+            amount: Uint128::new(300),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.abstain_votes, Uint128::zero());
+
+    // 600 of 900 votes is less than 70%
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env_height(
+            POLL_START_HEIGHT,
+            mock_env().block.time.seconds() + DEFAULT_VOTING_PERIOD,
+        ),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "end_poll"),
+            attr("poll_id", "1"),
+            attr("rejected_reason", "No winning option"),
+            attr("passed", "false"),
+        ]
+    );
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Rejected);
+    assert_eq!(poll_res.winning_option, None);
+}
+
+#[test]
+fn multi_choice_poll_tie_has_no_winner() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 500, 500);
+    stake_voting_tokens(&mut deps, TEST_VOTER_2, 500, 1000);
+    create_multi_choice_poll(&mut deps, None, three_poll_options(), None).unwrap();
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Choice { option: 0 }, 500).unwrap();
+    cast_vote(
+        &mut deps,
+        TEST_VOTER_2,
+        VoteOption::Choice { option: 1 },
+        500,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env_height(
+            POLL_START_HEIGHT,
+            mock_env().block.time.seconds() + DEFAULT_VOTING_PERIOD,
+        ),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes[2],
+        attr("rejected_reason", "No winning option")
+    );
+}
+
+#[test]
+fn fails_multi_choice_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+
+    let res = create_multi_choice_poll(
+        &mut deps,
+        Some(vec![burn_msg(1)]),
+        three_poll_options(),
+        None,
+    );
+    match res {
+        Err(ContractError::PollOptionsWithMessages) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let res = create_multi_choice_poll(&mut deps, None, three_poll_options()[..1].to_vec(), None);
+    match res {
+        Err(ContractError::TooFewPollOptions) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let mut options = three_poll_options();
+    options[2].name = "first".to_string();
+    let res = create_multi_choice_poll(&mut deps, None, options, None);
+    match res {
+        Err(ContractError::DuplicatedPollOption) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let res = create_multi_choice_poll(
+        &mut deps,
+        None,
+        three_poll_options(),
+        Some(WinningRule::MinimumShare {
+            share: Decimal::percent(101),
+        }),
+    );
+    match res {
+        Err(ContractError::InvalidWinningShare) => {}
+        _ => panic!("Must return error"),
+    }
+
+    create_multi_choice_poll(&mut deps, None, three_poll_options(), None).unwrap();
+    for vote in [VoteOption::Yes, VoteOption::Choice { option: 3 }] {
+        match cast_vote(&mut deps, TEST_VOTER, vote, 100) {
+            Err(ContractError::InvalidVoteOption) => {}
+            _ => panic!("Must return error"),
+        }
+    }
+}
+
+fn split_vote(allocations: Vec<(VoteOption, Decimal)>) -> VoteOption {
+    VoteOption::Split {
+        allocations: allocations
+            .into_iter()
+            .map(|(option, weight)| WeightedVoteOption { option, weight })
+            .collect(),
+    }
+}
+
+#[test]
+fn split_vote_updates_tallies_by_weight() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 101, 101);
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let vote = split_vote(vec![
+        (VoteOption::Yes, Decimal::percent(50)),
+        (VoteOption::No, Decimal::percent(25)),
+        (VoteOption::Abstain, Decimal::percent(25)),
+    ]);
+    let execute_res = cast_vote(&mut deps, TEST_VOTER, vote.clone(), 101).unwrap();
+    assert_eq!(
+        execute_res.attributes[4],
+        attr("vote_option", "yes:0.5,no:0.25,abstain:0.25")
+    );
+
+    // rounding remainder goes to the last option
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.yes_votes, Uint128::new(50));
+    assert_eq!(poll_res.no_votes, Uint128::new(25));
+    assert_eq!(poll_res.abstain_votes, Uint128::new(26));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Voters {
+            poll_id: 1,
+            start_after: None,
+            limit: None,
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: VotersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.voters,
+        vec![VotersResponseItem {
+            voter: TEST_VOTER.to_string(),
+            vote,
+            balance: Uint128::new(101),
+        }]
+    );
+
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::RetractVote { poll_id: 1 },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.yes_votes, Uint128::zero());
+    assert_eq!(poll_res.no_votes, Uint128::zero());
+    assert_eq!(poll_res.abstain_votes, Uint128::zero());
+}
+
+#[test]
+fn split_vote_of_delegate_is_recounted_on_override() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    // delegate votes with own 100 and delegated 50 tokens
+    let vote = split_vote(vec![
+        (VoteOption::Yes, Decimal::percent(60)),
+        (VoteOption::No, Decimal::percent(40)),
+    ]);
+    cast_vote(&mut deps, TEST_VOTER, vote, 150).unwrap();
+    assert_eq!(query_poll_votes(&deps), (90, 60));
+
+    cast_vote(&mut deps, TEST_VOTER_2, VoteOption::No, 50).unwrap();
+    assert_eq!(query_poll_votes(&deps), (60, 40 + 50));
+}
+
+#[test]
+fn fails_split_vote() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let vote = split_vote(vec![
+        (VoteOption::Yes, Decimal::percent(60)),
+        (VoteOption::No, Decimal::percent(30)),
+    ]);
+    match cast_vote(&mut deps, TEST_VOTER, vote, 100) {
+        Err(ContractError::InvalidSplitWeights) => {}
+        _ => panic!("Must return error"),
+    }
+
+    for vote in [
+        split_vote(vec![
+            (VoteOption::Yes, Decimal::percent(60)),
+            (VoteOption::Yes, Decimal::percent(40)),
+        ]),
+        split_vote(vec![
+            (VoteOption::Yes, Decimal::percent(60)),
+            (VoteOption::Choice { option: 0 }, Decimal::percent(40)),
+        ]),
+        split_vote(vec![]),
+    ] {
+        match cast_vote(&mut deps, TEST_VOTER, vote, 100) {
+            Err(ContractError::InvalidVoteOption) => {}
+            _ => panic!("Must return error"),
+        }
+    }
+}
+
+// returns voter address derived from the signing key and the message to cast the signed vote
+fn sign_vote(signing_key: &SigningKey, vote: SignedVote) -> (String, ExecuteMsg) {
+    let public_key = VerifyingKey::from(signing_key).to_bytes();
+    let signature: Signature =
+        signing_key.sign_digest(Sha256::new().chain(to_binary(&vote).unwrap().as_slice()));
+    let voter =
+        crate::utils::address_from_public_key(&WasmMockApi::default(), &public_key).unwrap();
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CastVoteBySignature {
+            public_key: Binary::from(public_key.as_slice()),
+            vote,
+            signature: Binary::from(signature.as_ref()),
+        },
+    };
+    (voter.to_string(), msg)
+}
+
+fn signed_vote(nonce: u64) -> SignedVote {
+    SignedVote {
+        poll_id: 1,
+        vote: VoteOption::Yes,
+        amount: Uint128::new(100),
+        nonce,
+        chain_id: mock_env().block.chain_id,
+        contract: MOCK_CONTRACT_ADDR.to_string(),
+    }
+}
+
+#[test]
+fn cast_vote_by_signature() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+    let (voter, msg) = sign_vote(&signing_key, signed_vote(0));
+    assert!(voter.starts_with(ACCOUNT_PREFIX));
+
+    stake_voting_tokens(&mut deps, &voter, 100, 100);
+    let poll_msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        poll_msg,
+    )
+    .unwrap();
+
+    // relayer submits the vote and the voter tokens are used
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER_2, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "cast_vote"),
+            attr("poll_id", "1"),
+            attr("amount", "100"),
+            attr("voter", voter.as_str()),
+            attr("vote_option", "yes"),
+            attr("relayer", TEST_VOTER_2),
+            attr("nonce", "0"),
+        ]
+    );
+    assert_eq!(query_poll_votes(&deps), (100, 0));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::VoteNonce {
+            address: voter.clone(),
+        },
+    )
+    .unwrap();
+    let response: VoteNonceResponse = from_binary(&res).unwrap();
+    assert_eq!(response.nonce, 1);
+
+    // the same signed vote can not be replayed
+    match execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg) {
+        Err(ContractError::InvalidVoteNonce) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+#[test]
+fn fails_cast_vote_by_signature() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]).unwrap();
+
+    let (_, msg) = sign_vote(
+        &signing_key,
+        SignedVote {
+            chain_id: "another-chain".to_string(),
+            ..signed_vote(0)
+        },
+    );
+    match execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg) {
+        Err(ContractError::VoteForAnotherContract) => {}
+        _ => panic!("Must return error"),
+    }
+
+    // vote is changed after signing
+    let (_, msg) = sign_vote(&signing_key, signed_vote(0));
+    let msg = match msg {
+        ExecuteMsg::Anyone {
+            anyone_msg:
+                AnyoneMsg::CastVoteBySignature {
+                    public_key,
+                    vote,
+                    signature,
+                },
+        } => ExecuteMsg::Anyone {
+            anyone_msg: AnyoneMsg::CastVoteBySignature {
+                public_key,
+                vote: SignedVote {
+                    amount: Uint128::new(1000),
+                    ..vote
+                },
+                signature,
+            },
+        },
+        _ => panic!("Must be signed vote"),
+    };
+    match execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg) {
+        Err(ContractError::InvalidVoteSignature) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let (_, msg) = sign_vote(&signing_key, signed_vote(1));
+    match execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg) {
+        Err(ContractError::InvalidVoteNonce) => {}
+        _ => panic!("Must return error"),
+    }
+}
+
+fn set_keeper_reward(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    keeper_reward: u128,
+    max_keeper_reward_per_poll: u128,
+) {
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::UpdateConfig {
+            quorum: None,
+            threshold: None,
+            voting_period: None,
+            timelock_period: None,
+            proposal_deposit: None,
+            snapshot_period: None,
+            unbonding_period: None,
+            expiration_period: None,
+            max_execution_retries: None,
+            keeper_reward: Some(Uint128::new(keeper_reward)),
+            max_keeper_reward_per_poll: Some(Uint128::new(max_keeper_reward_per_poll)),
+            forfeited_deposit_policy: None,
+            rejected_deposit_refund: None,
+            category_params: None,
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+}
+
+fn keeper_reward_msg(amount: u128) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: VOTING_TOKEN.to_string(),
+        funds: vec![],
+        msg: to_binary(&Cw20ExecuteMsg::Transfer {
+            recipient: TEST_VOTER_2.to_string(),
+            amount: Uint128::new(amount),
+        })
+        .unwrap(),
+    }))
+}
+
+#[test]
+fn keeper_is_paid_for_poll_lifecycle() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    set_keeper_reward(&mut deps, 10, 15);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // staked and deposited tokens can not be used for keeper rewards
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::new(1000 + DEFAULT_PROPOSAL_DEPOSIT + 100),
+        )],
+    )]);
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::AddKeeperRewards {
+            amount: Uint128::new(101),
+        },
+    };
+    match execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg) {
+        Err(ContractError::InsufficientFreeBalance(amount)) => {
+            assert_eq!(amount, Uint128::new(100))
+        }
+        _ => panic!("Must return error"),
+    }
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::AddKeeperRewards {
+            amount: Uint128::new(100),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 1000).unwrap();
+
+    let end_time = mock_env_poll_start()
+        .block
+        .time
+        .plus_seconds(DEFAULT_VOTING_PERIOD)
+        .seconds();
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::SnapshotPoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env_height(POLL_START_HEIGHT, end_time - DEFAULT_FIX_PERIOD),
+        mock_info(TEST_VOTER_2, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(execute_res.messages, vec![keeper_reward_msg(10)]);
+    assert_eq!(execute_res.attributes[3], attr("keeper_reward", "10"));
+
+    // reward is capped per poll
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env_height(POLL_START_HEIGHT, end_time),
+        mock_info(TEST_VOTER_2, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(execute_res.messages[1], keeper_reward_msg(5));
+    assert_eq!(execute_res.attributes[4], attr("keeper_reward", "5"));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.keeper_reward_paid, Uint128::new(15));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.keeper_reward_pool, Uint128::new(85));
+    assert_eq!(state.total_staked, Uint128::new(1000));
+    assert_eq!(state.total_deposit, Uint128::zero());
+}
+
+#[test]
+fn keeper_reward_is_limited_by_pool() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    set_keeper_reward(&mut deps, 10, 100);
+
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::new(DEFAULT_PROPOSAL_DEPOSIT + 3),
+        )],
+    )]);
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::AddKeeperRewards {
+            amount: Uint128::new(3),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    let end_time = mock_env_poll_start()
+        .block
+        .time
+        .plus_seconds(DEFAULT_VOTING_PERIOD)
+        .seconds();
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        mock_env_height(POLL_START_HEIGHT, end_time),
+        mock_info(TEST_VOTER_2, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(execute_res.messages, vec![keeper_reward_msg(3)]);
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.keeper_reward_pool, Uint128::zero());
+}
+
+#[test]
+fn keeper_is_paid_only_for_successful_execution() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let mut env = pass_poll_with_message(&mut deps);
+    env.block.time = env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
+    let mut config = load_config(&deps.storage).unwrap();
+    config.max_execution_retries = 1;
+    store_config(&mut deps.storage, &config).unwrap();
+    set_keeper_reward(&mut deps, 10, 100);
+
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(1000 + 100))],
+    )]);
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::AddKeeperRewards {
+            amount: Uint128::new(100),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg).unwrap();
+
+    // execution fails, poll messages are reverted together with the reward
+    let execute_msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ExecutePoll { poll_id: 1 },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
+        execute_msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(execute_res.messages.len(), 1);
+    let fail_reply = Reply {
+        id: POLL_EXECUTE_REPLY_ID,
+        result: ContractResult::Err("Error".to_string()),
+    };
+    reply(deps.as_mut(), mock_env(), fail_reply).unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::Failed);
+    assert_eq!(poll_res.keeper_reward_paid, Uint128::zero());
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.keeper_reward_pool, Uint128::new(100));
+
+    // retry succeeds and the keeper is paid
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER_2, &[]),
+        execute_msg,
+    )
+    .unwrap();
+    let msg = ExecuteMsg::Yourself {
+        yourself_msg: YourselfMsg::ExecutePollMsgs { poll_id: 1 },
+    };
+    let execute_res = execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap();
+    assert_eq!(execute_res.messages[1], keeper_reward_msg(10));
+    assert_eq!(execute_res.attributes[2], attr("keeper_reward", "10"));
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.keeper_reward_paid, Uint128::new(10));
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.keeper_reward_pool, Uint128::new(90));
+}
+
+fn set_deposit_policy(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    forfeited_deposit_policy: Option<ForfeitedDepositPolicy>,
+    rejected_deposit_refund: Option<Decimal>,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Governance {
+        governance_msg: GovernanceMsg::UpdateConfig {
+            quorum: None,
+            threshold: None,
+            voting_period: None,
+            timelock_period: None,
+            proposal_deposit: None,
+            snapshot_period: None,
+            unbonding_period: None,
+            expiration_period: None,
+            max_execution_retries: None,
+            keeper_reward: None,
+            max_keeper_reward_per_poll: None,
+            forfeited_deposit_policy,
+            rejected_deposit_refund,
+            category_params: None,
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_CREATOR, &[]), msg)
+}
+
+// creates poll, votes with all TEST_VOTER tokens if vote is set and ends the poll
+fn end_poll_with_vote(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    vote: Option<VoteOption>,
+) -> Response {
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    if let Some(vote) = vote {
+        cast_vote(deps, TEST_VOTER, vote, 1000).unwrap();
+    }
+
+    let end_time = mock_env_poll_start()
+        .block
+        .time
+        .plus_seconds(DEFAULT_VOTING_PERIOD)
+        .seconds();
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    execute(
+        deps.as_mut(),
+        mock_env_height(POLL_START_HEIGHT, end_time),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap()
+}
+
+fn deposit_msg(msg: Cw20ExecuteMsg) -> SubMsg {
+    SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: VOTING_TOKEN.to_string(),
+        funds: vec![],
+        msg: to_binary(&msg).unwrap(),
+    }))
+}
+
+#[test]
+fn forfeited_deposit_goes_to_stakers_by_default() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+
+    let execute_res = end_poll_with_vote(&mut deps, None);
+    assert!(execute_res.messages.is_empty());
+
+    let state = load_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_deposit, Uint128::zero());
+    assert_eq!(
+        state.total_staked,
+        Uint128::new(1000 + DEFAULT_PROPOSAL_DEPOSIT)
+    );
+}
+
+#[test]
+fn forfeited_deposit_is_sent_to_community_or_burned() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+    set_deposit_policy(
+        &mut deps,
+        Some(ForfeitedDepositPolicy::Community {
+            contract: "community".to_string(),
+        }),
+        None,
+    )
+    .unwrap();
+
+    let execute_res = end_poll_with_vote(&mut deps, None);
+    assert_eq!(
+        execute_res.messages,
+        vec![deposit_msg(Cw20ExecuteMsg::Transfer {
+            recipient: "community".to_string(),
+            amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+        })]
+    );
+    let state = load_state(deps.as_ref().storage).unwrap();
+    assert_eq!(state.total_staked, Uint128::new(1000));
+
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+    set_deposit_policy(&mut deps, Some(ForfeitedDepositPolicy::Burn), None).unwrap();
+
+    let execute_res = end_poll_with_vote(&mut deps, None);
+    assert_eq!(
+        execute_res.messages,
+        vec![deposit_msg(Cw20ExecuteMsg::Burn {
+            amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT),
+        })]
+    );
+}
+
+#[test]
+fn rejected_poll_gets_partial_deposit_refund() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+
+    let res = set_deposit_policy(&mut deps, None, Some(Decimal::percent(101)));
+    match res {
+        Err(ContractError::InvalidDepositRefund) => {}
+        _ => panic!("Must return error"),
+    }
+    set_deposit_policy(
+        &mut deps,
+        Some(ForfeitedDepositPolicy::Burn),
+        Some(Decimal::percent(40)),
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+    let config: ConfigResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        config.forfeited_deposit_policy,
+        ForfeitedDepositPolicy::Burn
+    );
+    assert_eq!(config.rejected_deposit_refund, Decimal::percent(40));
+
+    let execute_res = end_poll_with_vote(&mut deps, Some(VoteOption::No));
+    assert_eq!(
+        execute_res.attributes[2],
+        attr("rejected_reason", "Threshold not reached")
+    );
+    assert_eq!(
+        execute_res.attributes[4],
+        attr(
+            "forfeited_deposit",
+            (DEFAULT_PROPOSAL_DEPOSIT * 6 / 10).to_string()
+        )
+    );
+    assert_eq!(
+        execute_res.messages,
+        vec![
+            deposit_msg(Cw20ExecuteMsg::Transfer {
+                recipient: TEST_CREATOR.to_string(),
+                amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT * 4 / 10),
+            }),
+            deposit_msg(Cw20ExecuteMsg::Burn {
+                amount: Uint128::new(DEFAULT_PROPOSAL_DEPOSIT * 6 / 10),
+            }),
+        ]
+    );
+}
+
+fn query_voter_polls(
+    deps: &OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    address: &str,
+    start_after: Option<u64>,
+    order_by: Option<OrderBy>,
+) -> VoterPollsResponse {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::VoterPolls {
+            address: address.to_string(),
+            start_after,
+            limit: None,
+            order_by,
+        },
+    )
+    .unwrap();
+    from_binary(&res).unwrap()
+}
+
+fn query_voter_stats(
+    deps: &OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    address: &str,
+) -> VoterStatsResponse {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::VoterStats {
+            address: address.to_string(),
+        },
+    )
+    .unwrap();
+    from_binary(&res).unwrap()
+}
+
+#[test]
+fn voter_polls_kept_after_poll_end() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000 + DEFAULT_PROPOSAL_DEPOSIT);
+    end_poll_with_vote(&mut deps, Some(VoteOption::Yes));
+
+    // tokens are unlocked after the poll end
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::WithdrawVotingTokens {
+            amount: Some(Uint128::new(1000)),
+        },
+    };
+    let end_time = mock_env_poll_start().block.time.seconds() + DEFAULT_VOTING_PERIOD;
+    let env = mock_env_height(POLL_START_HEIGHT, end_time);
+    execute(deps.as_mut(), env, mock_info(TEST_VOTER, &[]), msg).unwrap();
+
+    assert_eq!(
+        query_voter_polls(&deps, TEST_VOTER, None, None),
+        VoterPollsResponse {
+            polls: vec![VoterPollsResponseItem {
+                poll_id: 1,
+                vote: VoteOption::Yes,
+                balance: Uint128::new(1000),
+                status: PollStatus::Passed,
+            }],
+        }
+    );
+    assert_eq!(
+        query_voter_stats(&deps, TEST_VOTER),
+        VoterStatsResponse {
+            polls_voted: 1,
+            total_weight: Uint128::new(1000),
+        }
+    );
+    assert_eq!(
+        query_voter_stats(&deps, TEST_VOTER_2),
+        VoterStatsResponse {
+            polls_voted: 0,
+            total_weight: Uint128::zero(),
+        }
+    );
+}
+
+#[test]
+fn voter_polls_follow_vote_changes() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(
+        &mut deps,
+        TEST_VOTER,
+        100,
+        100 + 2 * DEFAULT_PROPOSAL_DEPOSIT,
+    );
+    for _ in 0..2 {
+        let msg = create_poll_msg("test", "test", None, None, None);
+        execute(
+            deps.as_mut(),
+            mock_env_poll_start(),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+    }
+
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 60).unwrap();
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CastVote {
+            poll_id: 2,
+            vote: VoteOption::No,
+            amount: Uint128::new(30),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    assert_eq!(
+        query_voter_stats(&deps, TEST_VOTER),
+        VoterStatsResponse {
+            polls_voted: 2,
+            total_weight: Uint128::new(90),
+        }
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        change_vote_msg(VoteOption::Abstain, 40),
+    )
+    .unwrap();
+    let polls = query_voter_polls(&deps, TEST_VOTER, None, Some(OrderBy::Asc)).polls;
+    assert_eq!(
+        polls
+            .iter()
+            .map(|item| (item.poll_id, item.vote.clone(), item.balance.u128()))
+            .collect::<Vec<_>>(),
+        vec![(1, VoteOption::Abstain, 40), (2, VoteOption::No, 30)]
+    );
+    assert_eq!(
+        query_voter_polls(&deps, TEST_VOTER, Some(2), None).polls[0].poll_id,
+        1
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        retract_vote_msg(),
+    )
+    .unwrap();
+    let polls = query_voter_polls(&deps, TEST_VOTER, None, None).polls;
+    assert_eq!(polls.len(), 1);
+    assert_eq!(polls[0].poll_id, 2);
+    assert_eq!(
+        query_voter_stats(&deps, TEST_VOTER),
+        VoterStatsResponse {
+            polls_voted: 1,
+            total_weight: Uint128::new(30),
+        }
+    );
+}
+
+#[test]
+fn query_stakers() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Stakers {
+            start_after: None,
+            limit: None,
+            order_by: Some(OrderBy::Asc),
+        },
+    )
+    .unwrap();
+    let response: StakersResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.stakers,
+        vec![
+            StakersResponseItem {
+                staker: TEST_VOTER.to_string(),
+                share: Uint128::new(100),
+                balance: Uint128::new(100),
+            },
+            StakersResponseItem {
+                staker: TEST_VOTER_2.to_string(),
+                share: Uint128::new(50),
+                balance: Uint128::new(50),
+            },
+        ]
+    );
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Stakers {
+            start_after: Some(TEST_VOTER_2.to_string()),
+            limit: Some(1),
+            order_by: None,
+        },
+    )
+    .unwrap();
+    let response: StakersResponse = from_binary(&res).unwrap();
+    assert_eq!(response.stakers.len(), 1);
+    assert_eq!(response.stakers[0].staker, TEST_VOTER);
+
+    // stakers who withdrew everything are not listed
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::WithdrawVotingTokens { amount: None },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Stakers {
+            start_after: None,
+            limit: Some(1),
+            order_by: Some(OrderBy::Asc),
+        },
+    )
+    .unwrap();
+    let response: StakersResponse = from_binary(&res).unwrap();
+    assert_eq!(response.stakers.len(), 1);
+    assert_eq!(response.stakers[0].staker, TEST_VOTER_2);
+}
+
+fn query_voting_power(
+    deps: &OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    address: &str,
+    poll_id: Option<u64>,
+) -> (u128, u128) {
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::VotingPower {
+            address: address.to_string(),
+            poll_id,
+        },
+    )
+    .unwrap();
+    let response: VotingPowerResponse = from_binary(&res).unwrap();
+    assert_eq!(
+        response.voting_power,
+        response.own_balance + response.delegated_balance
+    );
+    (
+        response.own_balance.u128(),
+        response.delegated_balance.u128(),
+    )
+}
+
+#[test]
+fn query_voting_power_in_poll() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, None), (100, 50));
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, Some(1)), (100, 50));
+    assert_eq!(query_voting_power(&deps, TEST_VOTER_2, None), (50, 0));
+    assert_eq!(query_voting_power(&deps, TEST_VOTER_3, None), (0, 0));
+
+    // delegator vote takes its share from the delegate voting power in this poll only
+    cast_vote(&mut deps, TEST_VOTER_2, VoteOption::No, 50).unwrap();
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, Some(1)), (100, 0));
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, None), (100, 50));
+}
+
+#[test]
+fn query_total_voting_power() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+
+    for (at, expected) in [
+        (None, 150u128),
+        (Some(mock_env().block.height), 0),
+        (Some(POLL_START_HEIGHT), 150),
+    ] {
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::TotalVotingPower { at }).unwrap();
+        let response: TotalVotingPowerResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            response,
+            TotalVotingPowerResponse {
+                total_share: Uint128::new(expected),
+                total_balance: Uint128::new(expected),
+            }
+        );
+    }
+}
+
+#[test]
+fn stake_voting_tokens_for_recipient() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(&MOCK_CONTRACT_ADDR.to_string(), &Uint128::new(100))],
+    )]);
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: TEST_CREATOR.to_string(),
+        amount: Uint128::new(100),
+        msg: to_binary(&Cw20HookMsg::StakeVotingTokens {
+            recipient: Some(TEST_VOTER.to_string()),
+        })
+        .unwrap(),
+    });
+    let execute_res =
+        execute(deps.as_mut(), mock_env(), mock_info(VOTING_TOKEN, &[]), msg).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "staking"),
+            attr("sender", TEST_CREATOR),
+            attr("share", "100"),
+            attr("amount", "100"),
+            attr("recipient", TEST_VOTER),
+        ]
+    );
+
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, None), (100, 0));
+    assert_eq!(query_voting_power(&deps, TEST_CREATOR, None), (0, 0));
+}
+
+fn transfer_stake_msg(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    sender: &str,
+    recipient: &str,
+    amount: u128,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::TransferStake {
+            recipient: recipient.to_string(),
+            amount: Uint128::new(amount),
+        },
+    };
+    let env = mock_env_height(POLL_START_HEIGHT + 1, mock_env().block.time.seconds());
+    execute(deps.as_mut(), env, mock_info(sender, &[]), msg)
+}
+
+#[test]
+fn transfer_stake_moves_unlocked_tokens() {
+    let mut deps = mock_dependencies(&[]);
+    mock_delegation(&mut deps);
+    cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 60).unwrap();
+
+    match transfer_stake_msg(&mut deps, TEST_VOTER, TEST_VOTER, 10) {
+        Err(ContractError::SelfTransfer) => {}
+        _ => panic!("Must return error"),
+    }
+    match transfer_stake_msg(&mut deps, TEST_VOTER, TEST_VOTER_3, 0) {
+        Err(ContractError::InsufficientFunds) => {}
+        _ => panic!("Must return error"),
+    }
+    match transfer_stake_msg(&mut deps, TEST_VOTER_3, TEST_VOTER, 10) {
+        Err(ContractError::NothingStaked) => {}
+        _ => panic!("Must return error"),
+    }
+    // tokens voted in the poll in progress are locked
+    match transfer_stake_msg(&mut deps, TEST_VOTER, TEST_VOTER_3, 50) {
+        Err(ContractError::WithdrawTooMany) => {}
+        _ => panic!("Must return error"),
+    }
+
+    let execute_res = transfer_stake_msg(&mut deps, TEST_VOTER, TEST_VOTER_3, 40).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "transfer_stake"),
+            attr("sender", TEST_VOTER),
+            attr("recipient", TEST_VOTER_3),
+            attr("share", "40"),
+            attr("amount", "40"),
+        ]
+    );
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, None), (60, 50));
+    assert_eq!(query_voting_power(&deps, TEST_VOTER_3, None), (40, 0));
+
+    // delegated voting power follows the transferred tokens
+    transfer_stake_msg(&mut deps, TEST_VOTER_2, TEST_VOTER_3, 20).unwrap();
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, None), (60, 30));
+    assert_eq!(query_voting_power(&deps, TEST_VOTER_3, None), (60, 0));
+
+    // voting power in the already created poll can only decrease
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, Some(1)), (60, 30));
+    assert_eq!(query_voting_power(&deps, TEST_VOTER_3, Some(1)), (0, 0));
+}
+
+#[test]
+fn cast_vote_is_capped_at_current_stake() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 100, 100);
+
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let withdraw_msg = |amount: u128| ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::WithdrawVotingTokens {
+            amount: Some(Uint128::new(amount)),
+        },
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        withdraw_msg(60),
+    )
+    .unwrap();
+
+    // withdrawn tokens can not vote in the poll created before the withdrawal
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, Some(1)), (40, 0));
+    assert_eq!(
+        cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 41).unwrap_err(),
+        ContractError::InsufficientStake
+    );
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        withdraw_msg(40),
+    )
+    .unwrap();
+    assert_eq!(
+        cast_vote(&mut deps, TEST_VOTER, VoteOption::Yes, 100).unwrap_err(),
+        ContractError::InsufficientStake
+    );
+
+    // rejected vote does not lock tokens the staker does not have
+    assert_eq!(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(TEST_VOTER, &[]),
+            withdraw_msg(1),
+        )
+        .unwrap_err(),
+        ContractError::NothingStaked
+    );
+    assert_eq!(
+        transfer_stake_msg(&mut deps, TEST_VOTER, TEST_VOTER_2, 1).unwrap_err(),
+        ContractError::NothingStaked
+    );
+}
+
+#[test]
+fn withdraw_after_voting_in_many_polls() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+
+    // every poll ends at its own time, so each vote is a separate lock
+    let polls_count = 25u64;
+    let mut env = mock_env_poll_start();
+    for poll_id in 1..=polls_count {
+        env.block.time = env.block.time.plus_seconds(1);
+        let msg = create_poll_msg("test", "test", None, None, None);
+        execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(VOTING_TOKEN, &[]),
+            msg,
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Anyone {
+            anyone_msg: AnyoneMsg::CastVote {
+                poll_id,
+                vote: VoteOption::Yes,
+                amount: Uint128::new(poll_id as u128 * 10),
+            },
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+    }
+    assert_eq!(
+        read_voter_poll_locks(&deps.storage, &Addr::unchecked(TEST_VOTER), 0)
+            .unwrap()
+            .len(),
+        polls_count as usize
+    );
+
+    // tokens are locked till the last poll ends
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::WithdrawVotingTokens { amount: None },
+    };
+    let execute_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(execute_res.attributes[2], attr("amount", "750"));
+
+    // only a limited number of expired locks is removed by a withdrawal
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let execute_res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_VOTER, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(execute_res.attributes[2], attr("amount", "250"));
+    assert_eq!(
+        read_voter_poll_locks(&deps.storage, &Addr::unchecked(TEST_VOTER), 0)
+            .unwrap()
+            .len(),
+        15
+    );
+}
+
+fn ownership_msg(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+    env: Env,
+    sender: &str,
+    ownership_msg: OwnershipMsg,
+) -> Result<Response, ContractError> {
+    let msg = ExecuteMsg::Ownership { ownership_msg };
+    execute(deps.as_mut(), env, mock_info(sender, &[]), msg)
+}
+
+#[test]
+fn ownership_proposal_can_be_cancelled_or_expire() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    let propose_msg = OwnershipMsg::ProposeNewOwner {
+        owner: TEST_VOTER.to_string(),
+        expires_in: Some(100),
+    };
+
+    let res = ownership_msg(&mut deps, mock_env(), TEST_VOTER, propose_msg.clone());
+    assert_eq!(res, Err(ContractError::Unauthorized));
+
+    let execute_res =
+        ownership_msg(&mut deps, mock_env(), TEST_CREATOR, propose_msg.clone()).unwrap();
+    let expires_at = mock_env().block.time.seconds() + 100;
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "propose_new_owner"),
+            attr("new_owner", TEST_VOTER),
+            attr("expires_at", expires_at.to_string()),
+        ]
+    );
+    let res = ownership_msg(
+        &mut deps,
+        mock_env(),
+        TEST_VOTER_2,
+        OwnershipMsg::AcceptOwnership {},
+    );
+    assert_eq!(res, Err(ContractError::Unauthorized));
+
+    ownership_msg(
+        &mut deps,
+        mock_env(),
+        TEST_CREATOR,
+        OwnershipMsg::CancelOwnershipProposal {},
+    )
+    .unwrap();
+    let res = ownership_msg(
+        &mut deps,
+        mock_env(),
+        TEST_VOTER,
+        OwnershipMsg::AcceptOwnership {},
+    );
+    assert_eq!(res, Err(ContractError::NoOwnershipProposal));
+
+    ownership_msg(&mut deps, mock_env(), TEST_CREATOR, propose_msg).unwrap();
+    let res = ownership_msg(
+        &mut deps,
+        mock_env_height(mock_env().block.height, expires_at + 1),
+        TEST_VOTER,
+        OwnershipMsg::AcceptOwnership {},
+    );
+    assert_eq!(res, Err(ContractError::OwnershipProposalExpired));
+    assert_eq!(load_config(&deps.storage).unwrap().owner, TEST_CREATOR);
+}
+
+fn migrate_contract_msg(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+) -> Result<Response, ContractError> {
+    migrate(
+        deps.as_mut(),
+        mock_env(),
+        services::governance::MigrateMsg {},
+    )
+}
+
+#[test]
+fn migrate_checks_contract_name_and_version() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+
+    let execute_res = migrate_contract_msg(&mut deps).unwrap();
+    assert_eq!(
+        execute_res.attributes,
+        vec![
+            attr("action", "migrate"),
+            attr("previous_version", CONTRACT_VERSION),
+            attr("new_version", CONTRACT_VERSION),
+        ]
+    );
+
+    init_contract_version(&mut deps.storage, CONTRACT_NAME, "99.0.0").unwrap();
+    let res = migrate_contract_msg(&mut deps);
+    assert_eq!(
+        res,
+        Err(ContractError::Std(StdError::generic_err(format!(
+            "Can not downgrade {} from 99.0.0 to {}",
+            CONTRACT_NAME, CONTRACT_VERSION
+        ))))
+    );
+
+    init_contract_version(&mut deps.storage, "crates.io:nexus-staking", "0.1.0").unwrap();
+    let res = migrate_contract_msg(&mut deps);
+    assert_eq!(
+        res,
+        Err(ContractError::Std(StdError::generic_err(format!(
+            "Can not migrate crates.io:nexus-staking to {}",
+            CONTRACT_NAME
+        ))))
+    );
+}
+
+fn migration_1_1_0(deps: DepsMut, _env: &Env) -> StdResult<()> {
+    deps.storage.set(b"migration_1_1_0", b"done");
+    Ok(())
+}
+
+fn migration_1_2_0(deps: DepsMut, _env: &Env) -> StdResult<()> {
+    deps.storage.set(b"migration_1_2_0", b"done");
+    Ok(())
+}
+
+fn migration_2_0_0(deps: DepsMut, _env: &Env) -> StdResult<()> {
+    deps.storage.set(b"migration_2_0_0", b"done");
+    Ok(())
+}
+
+#[test]
+fn migrate_runs_only_new_migrations() {
+    let mut deps = mock_dependencies(&[]);
+    init_contract_version(&mut deps.storage, CONTRACT_NAME, "1.1.0").unwrap();
+
+    let migrations: &[(&str, MigrationFn)] = &[
+        ("1.1.0", migration_1_1_0),
+        ("1.2.0", migration_1_2_0),
+        ("2.0.0", migration_2_0_0),
+    ];
+    migrate_contract(
+        deps.as_mut(),
+        &mock_env(),
+        CONTRACT_NAME,
+        "1.2.0",
+        migrations,
+    )
+    .unwrap();
+    assert_eq!(deps.storage.get(b"migration_1_1_0"), None);
+    assert_eq!(deps.storage.get(b"migration_1_2_0"), Some(b"done".to_vec()));
+    assert_eq!(deps.storage.get(b"migration_2_0_0"), None);
+
+    // contract without cw2 metadata runs all migrations up to the new version
+    let mut deps = mock_dependencies(&[]);
+    migrate_contract(
+        deps.as_mut(),
+        &mock_env(),
+        CONTRACT_NAME,
+        "2.0.0",
+        migrations,
+    )
+    .unwrap();
+    assert_eq!(deps.storage.get(b"migration_1_1_0"), Some(b"done".to_vec()));
+    assert_eq!(deps.storage.get(b"migration_2_0_0"), Some(b"done".to_vec()));
+}
+
+#[test]
+fn migrate_from_1_0_0_storage() {
+    let mut deps = mock_dependencies(&[]);
+    let end_time = mock_env().block.time.seconds() + DEFAULT_VOTING_PERIOD;
+    let burn_msg = to_binary(&Cw20ExecuteMsg::Burn {
+        amount: Uint128::new(1),
+    })
+    .unwrap();
+
+    // storage written by the 1.0.0 release, without cw2 metadata
+    deps.storage.set(
+        b"config",
+        br#"{"owner":"creator","psi_token":"voting_token","quorum":"0.3","threshold":"0.5","voting_period":20000,"timelock_period":10000,"proposal_deposit":"10000000000","snapshot_period":10}"#,
+    );
+    deps.storage.set(
+        b"state",
+        br#"{"poll_count":1,"total_share":"150","total_deposit":"10000000000"}"#,
+    );
+    deps.storage.set(
+        &Map::<U64Key, ()>::new("poll").key(1u64.into()),
+        format!(
+            r#"{{"id":1,"creator":"creator","status":"in_progress","yes_votes":"60","no_votes":"0","end_time":{},"title":"test","description":"test","link":null,"execute_data":[{{"order":1,"contract":"voting_token","msg":"{}"}}],"migrate_data":null,"deposit_amount":"10000000000","total_balance_at_end_poll":null,"staked_amount":null}}"#,
+            end_time,
+            burn_msg.to_base64()
+        )
+        .as_bytes(),
+    );
+    deps.storage.set(
+        &Map::<&Addr, ()>::new("bank").key(&Addr::unchecked(TEST_VOTER)),
+        br#"{"share":"100","locked_balance":[[1,{"vote":"yes","balance":"60"}]]}"#,
+    );
+    deps.storage.set(
+        &Map::<&Addr, ()>::new("bank").key(&Addr::unchecked(TEST_VOTER_2)),
+        br#"{"share":"50","locked_balance":[]}"#,
+    );
+    deps.querier.with_token_balances(&[(
+        &VOTING_TOKEN.to_string(),
+        &[(
+            &MOCK_CONTRACT_ADDR.to_string(),
+            &Uint128::new(150 + DEFAULT_PROPOSAL_DEPOSIT),
+        )],
+    )]);
+
+    migrate_contract_msg(&mut deps).unwrap();
+
+    // polls and stakers are migrated in batches, nothing else can be done meanwhile
+    let withdraw_msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::WithdrawVotingTokens {
+            amount: Some(Uint128::new(41)),
+        },
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        withdraw_msg.clone(),
+    );
+    assert_eq!(res.unwrap_err(), ContractError::MigrationInProgress);
+    for (limit, migrated, finished) in [(1, "1", "false"), (10, "2", "true")] {
+        let msg = ExecuteMsg::Anyone {
+            anyone_msg: AnyoneMsg::MigrateBatch { limit },
+        };
+        let execute_res =
+            execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg).unwrap();
+        assert_eq!(
+            execute_res.attributes,
+            vec![
+                attr("action", "migrate_batch"),
+                attr("migrated", migrated),
+                attr("finished", finished),
+            ]
+        );
+    }
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::MigrateBatch { limit: 10 },
+    };
+    let res = execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER_2, &[]), msg);
+    assert_eq!(res.unwrap_err(), ContractError::NothingToMigrate);
+
+    let config = load_config(&deps.storage).unwrap();
+    assert_eq!(config.psi_token, VOTING_TOKEN);
+    assert_eq!(config.expiration_period, DEFAULT_VOTING_PERIOD);
+    assert!(config.category_params.is_empty());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap();
+    let state: StateResponse = from_binary(&res).unwrap();
+    assert_eq!(state.total_share, Uint128::new(150));
+    assert_eq!(state.total_staked, Uint128::new(150));
+    assert_eq!(state.total_deposit, Uint128::new(DEFAULT_PROPOSAL_DEPOSIT));
+    assert_eq!(state.keeper_reward_pool, Uint128::zero());
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.status, PollStatus::InProgress);
+    assert_eq!(poll_res.category, PollCategory::TreasurySpend);
+    assert_eq!(poll_res.yes_votes, Uint128::new(60));
+    assert_eq!(poll_res.execute_data.unwrap()[0].msg, Some(burn_msg));
+
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Staker {
+            address: TEST_VOTER.to_string(),
+        },
+    )
+    .unwrap();
+    let staker_res: StakerResponse = from_binary(&res).unwrap();
+    assert_eq!(staker_res.balance, Uint128::new(100));
+    assert_eq!(
+        staker_res.locked_balance,
+        vec![(
+            1,
+            VoterInfo {
+                vote: VoteOption::Yes,
+                balance: Uint128::new(60),
+            }
+        )]
+    );
+
+    // votes of the locks are in the voter history
+    assert_eq!(
+        query_voter_polls(&deps, TEST_VOTER, None, None),
+        VoterPollsResponse {
+            polls: vec![VoterPollsResponseItem {
+                poll_id: 1,
+                vote: VoteOption::Yes,
+                balance: Uint128::new(60),
+                status: PollStatus::InProgress,
+            }],
+        }
+    );
+    assert_eq!(query_voter_stats(&deps, TEST_VOTER).polls_voted, 1);
+
+    // existing stakers keep their voting power
+    assert_eq!(query_voting_power(&deps, TEST_VOTER, Some(1)), (100, 0));
+    let res = query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::TotalVotingPower {
+            at: Some(mock_env().block.height),
+        },
+    )
+    .unwrap();
+    let response: TotalVotingPowerResponse = from_binary(&res).unwrap();
+    assert_eq!(response.total_share, Uint128::new(150));
+
+    // tokens locked by the vote stay locked after the migration
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(TEST_VOTER, &[]),
+        withdraw_msg,
+    );
+    assert_eq!(res.unwrap_err(), ContractError::WithdrawTooMany);
+
+    // polls created after the migration use the migrated checkpoints
+    let msg = create_poll_msg("test", "test", None, None, None);
+    execute(
+        deps.as_mut(),
+        mock_env_poll_start(),
+        mock_info(VOTING_TOKEN, &[]),
+        msg,
+    )
+    .unwrap();
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::CastVote {
+            poll_id: 2,
+            vote: VoteOption::Yes,
+            amount: Uint128::new(100),
+        },
+    };
+    execute(deps.as_mut(), mock_env(), mock_info(TEST_VOTER, &[]), msg).unwrap();
+}
+
+fn action_msg(order: u64, contract: &str, action: PollAction) -> PollExecuteMsg {
+    PollExecuteMsg {
+        order,
+        contract: contract.to_string(),
+        msg: None,
+        action: Some(action),
+        funds: vec![],
+    }
+}
+
+// TEST_VOTER passes poll 1 which is then executed
+fn pass_and_execute_poll(
+    deps: &mut OwnedDeps<MockStorage, WasmMockApi, WasmMockQuerier>,
+) -> Response {
+    cast_vote(deps, TEST_VOTER, VoteOption::Yes, 1000).unwrap();
+    let mut env = mock_env_poll_start();
+    env.block.time = env.block.time.plus_seconds(DEFAULT_VOTING_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::EndPoll { poll_id: 1 },
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    env.block.time = env.block.time.plus_seconds(DEFAULT_TIMELOCK_PERIOD);
+    let msg = ExecuteMsg::Anyone {
+        anyone_msg: AnyoneMsg::ExecutePoll { poll_id: 1 },
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(TEST_CREATOR, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::Yourself {
+        yourself_msg: YourselfMsg::ExecutePollMsgs { poll_id: 1 },
+    };
+    execute(deps.as_mut(), env, mock_info(MOCK_CONTRACT_ADDR, &[]), msg).unwrap()
+}
+
+#[test]
+fn poll_with_typed_actions() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+
+    let spend_action = PollAction::CommunitySpend {
+        recipient: TEST_VOTER.to_string(),
+        amount: Uint128::new(10),
+    };
+    let guardian_action = PollAction::Governance {
+        governance_msg: GovernanceMsg::UpdateGuardian {
+            guardian: Some(TEST_GUARDIAN.to_string()),
+        },
+    };
+    let execute_msgs = vec![
+        action_msg(1, "community", spend_action.clone()),
+        action_msg(2, MOCK_CONTRACT_ADDR, guardian_action.clone()),
+    ];
+    create_categorized_poll(
+        &mut deps,
+        Some(execute_msgs.clone()),
+        None,
+        None,
+        DEFAULT_PROPOSAL_DEPOSIT,
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    // governance rules are changed by the poll
+    assert_eq!(poll_res.category, PollCategory::CodeMigration);
+    assert_eq!(poll_res.execute_data, Some(execute_msgs));
+
+    let execute_res = pass_and_execute_poll(&mut deps);
+    assert_eq!(
+        execute_res.messages,
+        vec![
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: "community".to_string(),
+                    msg: to_binary(&services::community::ExecuteMsg::Governance {
+                        governance_msg: services::community::GovernanceMsg::Spend {
+                            recipient: TEST_VOTER.to_string(),
+                            amount: Uint128::new(10),
+                        },
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+                100
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                    msg: to_binary(&ExecuteMsg::Governance {
+                        governance_msg: GovernanceMsg::UpdateGuardian {
+                            guardian: Some(TEST_GUARDIAN.to_string()),
+                        },
+                    })
+                    .unwrap(),
+                    funds: vec![],
+                }),
+                101
+            ),
+        ]
+    );
+}
+
+#[test]
+fn fails_create_poll_invalid_typed_action() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+
+    let spend_action = PollAction::CommunitySpend {
+        recipient: TEST_VOTER.to_string(),
+        amount: Uint128::new(10),
+    };
+    let mut both = action_msg(1, "community", spend_action);
+    both.msg = Some(burn_msg(1).msg.unwrap());
+    let mut neither = burn_msg(1);
+    neither.msg = None;
+    let foreign_governance = action_msg(
+        1,
+        VOTING_TOKEN,
+        PollAction::Governance {
+            governance_msg: GovernanceMsg::UpdateGuardian { guardian: None },
+        },
+    );
+
+    for (execute_msg, expected) in [
+        (both, ContractError::InvalidPollMessage),
+        (neither, ContractError::InvalidPollMessage),
+        (foreign_governance, ContractError::InvalidPollActionContract),
+    ] {
+        let res = create_categorized_poll(
+            &mut deps,
+            Some(vec![execute_msg]),
+            None,
+            None,
+            DEFAULT_PROPOSAL_DEPOSIT,
+        );
+        assert_eq!(res.unwrap_err(), expected);
+    }
+}
+
+#[test]
+fn poll_sends_native_tokens() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+    stake_voting_tokens(&mut deps, TEST_VOTER, 1000, 1000);
+
+    let mut execute_with_funds = burn_msg(1);
+    execute_with_funds.funds = coins(100, "uusd");
+    let bank_send = action_msg(
+        2,
+        TEST_VOTER,
+        PollAction::BankSend {
+            amount: coins(50, "uluna"),
+        },
+    );
+    create_categorized_poll(
+        &mut deps,
+        Some(vec![execute_with_funds, bank_send]),
+        None,
+        None,
+        DEFAULT_PROPOSAL_DEPOSIT,
+    )
+    .unwrap();
+
+    let res = query(deps.as_ref(), mock_env(), QueryMsg::Poll { poll_id: 1 }).unwrap();
+    let poll_res: PollResponse = from_binary(&res).unwrap();
+    assert_eq!(poll_res.category, PollCategory::TreasurySpend);
+
+    let execute_res = pass_and_execute_poll(&mut deps);
+    assert_eq!(
+        execute_res.messages,
+        vec![
+            SubMsg::reply_on_success(
+                CosmosMsg::Wasm(WasmMsg::Execute {
+                    contract_addr: VOTING_TOKEN.to_string(),
+                    msg: burn_msg(1).msg.unwrap(),
+                    funds: coins(100, "uusd"),
+                }),
+                100
+            ),
+            SubMsg::reply_on_success(
+                CosmosMsg::Bank(BankMsg::Send {
+                    to_address: TEST_VOTER.to_string(),
+                    amount: coins(50, "uluna"),
+                }),
+                101
+            ),
+        ]
+    );
+}
+
+#[test]
+fn fails_create_poll_invalid_funds() {
+    let mut deps = mock_dependencies(&[]);
+    mock_init(&mut deps);
+
+    let with_funds = |funds: Vec<Coin>| {
+        let mut msg = burn_msg(1);
+        msg.funds = funds;
+        msg
+    };
+    let bank_send = |amount: Vec<Coin>| action_msg(1, TEST_VOTER, PollAction::BankSend { amount });
+    let mut bank_send_with_funds = bank_send(coins(1, "uluna"));
+    bank_send_with_funds.funds = coins(1, "uusd");
+
+    for (execute_msg, expected) in [
+        (
+            with_funds(coins(0, "uusd")),
+            ContractError::InvalidFunds("zero amount of uusd".to_string()),
+        ),
+        (
+            with_funds(coins(1, "1usd")),
+            ContractError::InvalidFunds("invalid denom 1usd".to_string()),
+        ),
+        (
+            with_funds(coins(1, "us")),
+            ContractError::InvalidFunds("invalid denom us".to_string()),
+        ),
+        (
+            with_funds(vec![coin(1, "uusd"), coin(2, "uusd")]),
+            ContractError::InvalidFunds("duplicated denom uusd".to_string()),
+        ),
+        (
+            bank_send(coins(0, "uluna")),
+            ContractError::InvalidFunds("zero amount of uluna".to_string()),
+        ),
+        (bank_send(vec![]), ContractError::InvalidBankSend),
+        (bank_send_with_funds, ContractError::InvalidBankSend),
+    ] {
+        let res = create_categorized_poll(
+            &mut deps,
+            Some(vec![execute_msg]),
+            None,
+            None,
+            DEFAULT_PROPOSAL_DEPOSIT,
+        );
+        assert_eq!(res.unwrap_err(), expected);
+    }
+
+    // ibc denoms are accepted
+    create_categorized_poll(
+        &mut deps,
+        Some(vec![with_funds(coins(
+            1,
+            "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2",
+        ))]),
+        None,
+        None,
+        DEFAULT_PROPOSAL_DEPOSIT,
+    )
+    .unwrap();
+}
@@ -1,39 +1,56 @@
 use crate::contract::{
-    MAX_DESC_LENGTH, MAX_LINK_LENGTH, MAX_TITLE_LENGTH, MIN_DESC_LENGTH, MIN_LINK_LENGTH,
-    MIN_TITLE_LENGTH,
+    MAX_DENOM_LENGTH, MAX_DESC_LENGTH, MAX_LINK_LENGTH, MAX_OPTION_NAME_LENGTH, MAX_POLL_OPTIONS,
+    MAX_TITLE_LENGTH, MIN_DENOM_LENGTH, MIN_DESC_LENGTH, MIN_LINK_LENGTH, MIN_OPTION_NAME_LENGTH,
+    MIN_POLL_OPTIONS, MIN_TITLE_LENGTH,
 };
 
-use cosmwasm_std::{Decimal, StdError, StdResult};
+use cosmwasm_std::{from_binary, Addr, Api, CanonicalAddr, Coin, Decimal};
+use cw20::Cw20ExecuteMsg;
+use ripemd160::Ripemd160;
+use services::{
+    airdrop::ExecuteMsg as AirdropExecuteMsg,
+    community::{ExecuteMsg as CommunityExecuteMsg, GovernanceMsg as CommunityGovernanceMsg},
+    governance::{
+        ExecuteMsg, GovernanceMsg, PollAction, PollCategory, PollCategoryParams, PollOption,
+        WinningRule,
+    },
+    staking::ExecuteMsg as StakingExecuteMsg,
+    vesting::ExecuteMsg as VestingExecuteMsg,
+};
+use sha2::{Digest, Sha256};
+
+use crate::error::ContractError;
+use crate::state::{ExecuteData, MigrateData, PollOptionData};
 
 /// validate_title returns an error if the title is invalid
-pub fn validate_title(title: &str) -> StdResult<()> {
+pub fn validate_title(title: &str) -> Result<(), ContractError> {
     if title.len() < MIN_TITLE_LENGTH {
-        Err(StdError::generic_err("Title too short"))
+        Err(ContractError::TitleTooShort)
     } else if title.len() > MAX_TITLE_LENGTH {
-        Err(StdError::generic_err("Title too long"))
+        Err(ContractError::TitleTooLong)
     } else {
         Ok(())
     }
 }
 
 /// validate_description returns an error if the description is invalid
-pub fn validate_description(description: &str) -> StdResult<()> {
+pub fn validate_description(description: &str) -> Result<(), ContractError> {
     if description.len() < MIN_DESC_LENGTH {
-        Err(StdError::generic_err("Description too short"))
+        Err(ContractError::DescriptionTooShort)
     } else if description.len() > MAX_DESC_LENGTH {
-        Err(StdError::generic_err("Description too long"))
+        Err(ContractError::DescriptionTooLong)
     } else {
         Ok(())
     }
 }
 
 /// validate_link returns an error if the link is invalid
-pub fn validate_link(link: &Option<String>) -> StdResult<()> {
+pub fn validate_link(link: &Option<String>) -> Result<(), ContractError> {
     if let Some(link) = link {
         if link.len() < MIN_LINK_LENGTH {
-            Err(StdError::generic_err("Link too short"))
+            Err(ContractError::LinkTooShort)
         } else if link.len() > MAX_LINK_LENGTH {
-            Err(StdError::generic_err("Link too long"))
+            Err(ContractError::LinkTooLong)
         } else {
             Ok(())
         }
@@ -42,11 +59,249 @@ pub fn validate_link(link: &Option<String>) -> StdResult<()> {
     }
 }
 
+/// validate_poll_options returns an error if the number of options
+/// or any option name is invalid
+pub fn validate_poll_options(options: &[PollOption]) -> Result<(), ContractError> {
+    if options.len() < MIN_POLL_OPTIONS {
+        return Err(ContractError::TooFewPollOptions);
+    }
+    if options.len() > MAX_POLL_OPTIONS {
+        return Err(ContractError::TooManyPollOptions);
+    }
+    for (i, option) in options.iter().enumerate() {
+        if options[..i].iter().any(|item| item.name == option.name) {
+            return Err(ContractError::DuplicatedPollOption);
+        }
+        if option.name.len() < MIN_OPTION_NAME_LENGTH {
+            return Err(ContractError::OptionNameTooShort);
+        }
+        if option.name.len() > MAX_OPTION_NAME_LENGTH {
+            return Err(ContractError::OptionNameTooLong);
+        }
+    }
+    Ok(())
+}
+
+/// validate_funds returns an error if any coin has an invalid denom,
+/// zero amount or is duplicated
+pub fn validate_funds(funds: &[Coin]) -> Result<(), ContractError> {
+    for (i, coin) in funds.iter().enumerate() {
+        if !is_valid_denom(&coin.denom) {
+            return Err(ContractError::InvalidFunds(format!(
+                "invalid denom {}",
+                coin.denom
+            )));
+        }
+        if coin.amount.is_zero() {
+            return Err(ContractError::InvalidFunds(format!(
+                "zero amount of {}",
+                coin.denom
+            )));
+        }
+        if funds[..i].iter().any(|item| item.denom == coin.denom) {
+            return Err(ContractError::InvalidFunds(format!(
+                "duplicated denom {}",
+                coin.denom
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Cosmos SDK denom format: [a-zA-Z][a-zA-Z0-9/:._-]{2,127}
+fn is_valid_denom(denom: &str) -> bool {
+    (MIN_DENOM_LENGTH..=MAX_DENOM_LENGTH).contains(&denom.len())
+        && denom.starts_with(|c: char| c.is_ascii_alphabetic())
+        && denom
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "/:._-".contains(c))
+}
+
+/// validate_winning_rule returns an error if the minimum share is invalid
+/// (we require 0-1)
+pub fn validate_winning_rule(winning_rule: &WinningRule) -> Result<(), ContractError> {
+    match winning_rule {
+        WinningRule::MinimumShare { share } if *share > Decimal::one() => {
+            Err(ContractError::InvalidWinningShare)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// winning_option returns the index of the option with the most votes
+/// if it satisfies the winning rule, tie has no winner
+pub fn winning_option(options: &[PollOptionData], winning_rule: &WinningRule) -> Option<u32> {
+    let (index, leader) = options
+        .iter()
+        .enumerate()
+        .max_by_key(|(_, option)| option.votes)?;
+    if leader.votes.is_zero()
+        || options
+            .iter()
+            .filter(|option| option.votes == leader.votes)
+            .count()
+            > 1
+    {
+        return None;
+    }
+
+    if let WinningRule::MinimumShare { share } = winning_rule {
+        let total_votes: u128 = options.iter().map(|option| option.votes.u128()).sum();
+        if Decimal::from_ratio(leader.votes, total_votes) < *share {
+            return None;
+        }
+    }
+
+    Some(index as u32)
+}
+
 /// validate_quorum returns an error if the quorum is invalid
 /// (we require 0-1)
-pub fn validate_quorum(quorum: Decimal) -> StdResult<()> {
+pub fn validate_quorum(quorum: Decimal) -> Result<(), ContractError> {
     if quorum > Decimal::one() {
-        Err(StdError::generic_err("quorum must be 0 to 1"))
+        Err(ContractError::InvalidQuorum)
+    } else {
+        Ok(())
+    }
+}
+
+/// validate_category_params returns an error if any category is duplicated
+/// or has invalid quorum or threshold
+pub fn validate_category_params(
+    category_params: &[PollCategoryParams],
+) -> Result<(), ContractError> {
+    for (i, category_params_item) in category_params.iter().enumerate() {
+        if category_params[..i]
+            .iter()
+            .any(|item| item.category == category_params_item.category)
+        {
+            return Err(ContractError::DuplicatedPollCategory);
+        }
+        validate_quorum(category_params_item.params.quorum)?;
+        validate_threshold(category_params_item.params.threshold)?;
+    }
+    Ok(())
+}
+
+/// poll_category returns the category of a poll by its messages,
+/// proposer can declare treasury spend for a poll which looks like parameter change.
+/// Changes of governance rules and of ownership of any protocol contract need the same
+/// support as code migration, otherwise a poll of a weaker category could lower
+/// the parameters of stricter ones or hand over protocol funds
+pub fn poll_category(
+    declared: Option<PollCategory>,
+    execute_data: &Option<Vec<ExecuteData>>,
+    migrate_data: &Option<Vec<MigrateData>>,
+    governance: &Addr,
+) -> Result<PollCategory, ContractError> {
+    let is_spend = |data: &ExecuteData| {
+        if !data.funds.is_empty() {
+            return true;
+        }
+        let msg = match &data.action {
+            Some(action) => {
+                return match action {
+                    PollAction::CommunitySpend { .. }
+                    | PollAction::AddStakingSchedules { .. }
+                    | PollAction::RegisterVestingAccounts { .. }
+                    | PollAction::RegisterMerkleRoot { .. }
+                    | PollAction::BankSend { .. } => true,
+                    PollAction::Governance { .. } => false,
+                }
+            }
+            None => data.msg.clone().unwrap_or_default(),
+        };
+        matches!(
+            from_binary(&msg),
+            Ok(CommunityExecuteMsg::Governance {
+                governance_msg: CommunityGovernanceMsg::Spend { .. }
+            })
+        ) || matches!(
+            from_binary(&msg),
+            Ok(Cw20ExecuteMsg::Transfer { .. })
+                | Ok(Cw20ExecuteMsg::Send { .. })
+                | Ok(Cw20ExecuteMsg::IncreaseAllowance { .. })
+                | Ok(Cw20ExecuteMsg::TransferFrom { .. })
+                | Ok(Cw20ExecuteMsg::SendFrom { .. })
+                | Ok(Cw20ExecuteMsg::Burn { .. })
+                | Ok(Cw20ExecuteMsg::BurnFrom { .. })
+                | Ok(Cw20ExecuteMsg::Mint { .. })
+        ) || matches!(
+            from_binary(&msg),
+            Ok(StakingExecuteMsg::AddSchedules { .. })
+        ) || matches!(
+            from_binary(&msg),
+            Ok(VestingExecuteMsg::RegisterVestingAccounts { .. })
+        ) || matches!(
+            from_binary(&msg),
+            Ok(AirdropExecuteMsg::RegisterMerkleRoot { .. })
+        )
+    };
+
+    let changes_governance = |data: &ExecuteData| {
+        let is_governance = &data.contract == governance;
+        let msg = match &data.action {
+            Some(PollAction::Governance { governance_msg }) => {
+                return is_governance
+                    && matches!(
+                        governance_msg,
+                        GovernanceMsg::UpdateConfig { .. } | GovernanceMsg::UpdateGuardian { .. }
+                    )
+            }
+            Some(_) => return false,
+            None => data.msg.clone().unwrap_or_default(),
+        };
+        (is_governance
+            && matches!(
+                from_binary(&msg),
+                Ok(ExecuteMsg::Governance {
+                    governance_msg: GovernanceMsg::UpdateConfig { .. }
+                        | GovernanceMsg::UpdateGuardian { .. }
+                })
+            ))
+            || matches!(from_binary(&msg), Ok(ExecuteMsg::Ownership { .. }))
+            || matches!(from_binary(&msg), Ok(StakingExecuteMsg::Ownership { .. }))
+            || matches!(from_binary(&msg), Ok(VestingExecuteMsg::Ownership { .. }))
+            || matches!(from_binary(&msg), Ok(AirdropExecuteMsg::Ownership { .. }))
+            || matches!(
+                from_binary(&msg),
+                Ok(CommunityExecuteMsg::Governance {
+                    governance_msg: CommunityGovernanceMsg::UpdateConfig {
+                        governance_contract_addr: Some(_),
+                    }
+                })
+            )
+    };
+
+    let inferred = if migrate_data.is_some() {
+        PollCategory::CodeMigration
+    } else if let Some(execute_data) = execute_data {
+        if execute_data.iter().any(changes_governance) {
+            PollCategory::CodeMigration
+        } else if execute_data.iter().any(is_spend) {
+            PollCategory::TreasurySpend
+        } else {
+            PollCategory::ParameterChange
+        }
+    } else {
+        PollCategory::Text
+    };
+
+    match declared {
+        None => Ok(inferred),
+        Some(declared) if declared == inferred => Ok(inferred),
+        Some(PollCategory::TreasurySpend) if inferred == PollCategory::ParameterChange => {
+            Ok(PollCategory::TreasurySpend)
+        }
+        Some(_) => Err(ContractError::PollCategoryMismatch),
+    }
+}
+
+/// validate_deposit_refund returns an error if the refunded share is invalid
+/// (we require 0-1)
+pub fn validate_deposit_refund(refund: Decimal) -> Result<(), ContractError> {
+    if refund > Decimal::one() {
+        Err(ContractError::InvalidDepositRefund)
     } else {
         Ok(())
     }
@@ -54,10 +309,19 @@ pub fn validate_quorum(quorum: Decimal) -> StdResult<()> {
 
 /// validate_threshold returns an error if the threshold is invalid
 /// (we require 0-1)
-pub fn validate_threshold(threshold: Decimal) -> StdResult<()> {
+pub fn validate_threshold(threshold: Decimal) -> Result<(), ContractError> {
     if threshold > Decimal::one() {
-        Err(StdError::generic_err("threshold must be 0 to 1"))
+        Err(ContractError::InvalidThreshold)
     } else {
         Ok(())
     }
 }
+
+/// address_from_public_key returns the account address of a compressed secp256k1 public key
+pub fn address_from_public_key(api: &dyn Api, public_key: &[u8]) -> Result<Addr, ContractError> {
+    if public_key.len() != 33 {
+        return Err(ContractError::UncompressedPublicKey);
+    }
+    let hash = Ripemd160::digest(&Sha256::digest(public_key));
+    Ok(api.addr_humanize(&CanonicalAddr::from(hash.as_slice()))?)
+}
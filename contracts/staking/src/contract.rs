@@ -7,6 +7,8 @@ use cosmwasm_std::{
     Storage, Uint128, WasmMsg,
 };
 
+use services::migration::{init_contract_version, migrate_contract, MigrationFn};
+use services::ownership::{execute_ownership_msg, may_load_pending_owner};
 use services::staking::{
     ConfigResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
     StakerInfoResponse, StakingSchedule, StateResponse,
@@ -23,6 +25,11 @@ use terraswap::asset::{Asset, AssetInfo, PairInfo};
 use terraswap::pair::ExecuteMsg as PairExecuteMsg;
 use terraswap::querier::{query_pair_info, query_token_balance};
 
+pub(crate) const CONTRACT_NAME: &str = "crates.io:nexus-staking";
+pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// (version, migration) pairs of releases which change the storage layout, sorted by version
+const MIGRATIONS: &[(&str, MigrationFn)] = &[];
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -50,6 +57,8 @@ pub fn instantiate(
         },
     )?;
 
+    init_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
 
@@ -63,9 +72,20 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
             assert_owner_privilege(deps.storage, deps.api, info.sender)?;
             add_schedules(deps, env, schedules)
         }
-        ExecuteMsg::UpdateOwner { owner } => {
-            assert_owner_privilege(deps.storage, deps.api, info.sender)?;
-            update_owner(deps, owner)
+        ExecuteMsg::Ownership { ownership_msg } => {
+            let owner = deps.api.addr_humanize(&read_config(deps.storage)?.owner)?;
+            Ok(execute_ownership_msg(
+                deps,
+                &env,
+                &info,
+                &owner,
+                ownership_msg,
+                |deps, new_owner| {
+                    let mut config = read_config(deps.storage)?;
+                    config.owner = deps.api.addr_canonicalize(new_owner.as_str())?;
+                    store_config(deps.storage, &config)
+                },
+            )?)
         }
         ExecuteMsg::MigrateStaking {
             new_staking_contract,
@@ -264,14 +284,6 @@ pub fn add_schedules(
     Ok(Response::new().add_attribute("action", "add_schedules"))
 }
 
-pub fn update_owner(deps: DepsMut, new_owner: String) -> StdResult<Response> {
-    let mut config = read_config(deps.storage)?;
-    config.owner = deps.api.addr_canonicalize(&new_owner)?;
-    store_config(deps.storage, &config)?;
-
-    Ok(Response::new().add_attribute("action", "update_owner"))
-}
-
 pub fn migrate_staking(
     deps: DepsMut,
     env: Env,
@@ -603,6 +615,7 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let state = read_config(deps.storage)?;
     let resp = ConfigResponse {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
+        pending_owner: may_load_pending_owner(deps.storage)?,
         psi_token: deps.api.addr_humanize(&state.psi_token)?.to_string(),
         staking_token: deps.api.addr_humanize(&state.staking_token)?.to_string(),
         terraswap_factory: deps
@@ -654,6 +667,6 @@ pub fn query_staker_info(
 }
 
 #[entry_point]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    migrate_contract(deps, &env, CONTRACT_NAME, CONTRACT_VERSION, MIGRATIONS)
 }
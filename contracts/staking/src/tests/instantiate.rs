@@ -1,7 +1,9 @@
 use crate::contract::{execute, instantiate, query};
 use crate::tests::mock_env_block_time;
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
-use cosmwasm_std::{from_binary, Decimal, StdError, Uint128};
+use cosmwasm_std::{from_binary, Addr, Decimal, StdError, Uint128};
+use services::migration::init_contract_version;
+use services::ownership::{OwnershipMsg, PendingOwner};
 use services::staking::{
     ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, StakingSchedule, StateResponse,
 };
@@ -30,6 +32,7 @@ fn proper_initialization() {
         config,
         ConfigResponse {
             owner: "owner0000".to_string(),
+            pending_owner: None,
             psi_token: "reward0000".to_string(),
             staking_token: "staking0000".to_string(),
             terraswap_factory: "terraswap_factory0000".to_string(),
@@ -71,12 +74,34 @@ fn change_owner() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let msg = ExecuteMsg::UpdateOwner {
-        owner: new_owner.clone(),
+    let msg = ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::ProposeNewOwner {
+            owner: new_owner.clone(),
+            expires_in: None,
+        },
     };
     let info = mock_info(&owner, &[]);
     let _res = execute(deps.as_mut(), mock_env(), info.clone(), msg.clone()).unwrap();
 
+    let config = from_binary::<ConfigResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(config.owner, owner);
+    assert_eq!(
+        config.pending_owner,
+        Some(PendingOwner {
+            owner: Addr::unchecked(&new_owner),
+            expires_at: None,
+        })
+    );
+
+    let accept_msg = ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::AcceptOwnership {},
+    };
+    let new_owner_info = mock_info(&new_owner, &[]);
+    let _res = execute(deps.as_mut(), mock_env(), new_owner_info, accept_msg).unwrap();
+
     assert_eq!(
         from_binary::<ConfigResponse>(
             &query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap()
@@ -84,6 +109,7 @@ fn change_owner() {
         .unwrap(),
         ConfigResponse {
             owner: new_owner.clone(),
+            pending_owner: None,
             psi_token: "psi_token".to_string(),
             staking_token: "staking0000".to_string(),
             terraswap_factory: "terraswap_factory0000".to_string(),
@@ -100,3 +126,30 @@ fn change_owner() {
         panic!("wrong error");
     }
 }
+
+#[test]
+fn migrate_checks_contract_name() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner0000".to_string(),
+        psi_token: "reward0000".to_string(),
+        staking_token: "staking0000".to_string(),
+        terraswap_factory: "terraswap_factory0000".to_string(),
+        distribution_schedule: vec![],
+    };
+    let info = mock_info("addr0000", &[]);
+    crate::contract::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    crate::contract::migrate(deps.as_mut(), mock_env(), services::staking::MigrateMsg {}).unwrap();
+
+    init_contract_version(&mut deps.storage, "crates.io:nexus-other", "1.0.0").unwrap();
+    let res = crate::contract::migrate(deps.as_mut(), mock_env(), services::staking::MigrateMsg {});
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Can not migrate crates.io:nexus-other to crates.io:nexus-staking"
+        ),
+        _ => panic!("Must return migration error"),
+    }
+}
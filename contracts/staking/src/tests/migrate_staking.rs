@@ -113,6 +113,7 @@ fn test_migrate_staking() {
         config,
         ConfigResponse {
             owner: owner.clone(),
+            pending_owner: None,
             psi_token: "reward0000".to_string(),
             staking_token: "staking0000".to_string(),
             terraswap_factory: "terraswap_factory0000".to_string(),
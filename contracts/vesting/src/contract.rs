@@ -11,11 +11,18 @@ use crate::state::{
 };
 use cw20::Cw20ExecuteMsg;
 use services::common::OrderBy;
+use services::migration::{init_contract_version, migrate_contract, MigrationFn};
+use services::ownership::{execute_ownership_msg, may_load_pending_owner};
 use services::vesting::{
     ClaimableAmountResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg,
     VestingAccount, VestingAccountResponse, VestingAccountsResponse, VestingInfo, VestingSchedule,
 };
 
+pub(crate) const CONTRACT_NAME: &str = "crates.io:nexus-vesting";
+pub(crate) const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
+/// (version, migration) pairs of releases which change the storage layout, sorted by version
+const MIGRATIONS: &[(&str, MigrationFn)] = &[];
+
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
     deps: DepsMut,
@@ -32,6 +39,8 @@ pub fn instantiate(
         },
     )?;
 
+    init_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
     Ok(Response::default())
 }
 
@@ -41,12 +50,26 @@ pub fn execute(deps: DepsMut, env: Env, info: MessageInfo, msg: ExecuteMsg) -> S
         ExecuteMsg::Claim {} => claim(deps, env, info.sender.to_string()),
         ExecuteMsg::ClaimFor { address } => claim(deps, env, address),
         ExecuteMsg::UpdateConfig {
-            owner,
             psi_token,
             genesis_time,
         } => {
             assert_owner_privilege(deps.storage, deps.api, info.sender)?;
-            update_config(deps, owner, psi_token, genesis_time)
+            update_config(deps, psi_token, genesis_time)
+        }
+        ExecuteMsg::Ownership { ownership_msg } => {
+            let owner = deps.api.addr_humanize(&read_config(deps.storage)?.owner)?;
+            Ok(execute_ownership_msg(
+                deps,
+                &env,
+                &info,
+                &owner,
+                ownership_msg,
+                |deps, new_owner| {
+                    let mut config = read_config(deps.storage)?;
+                    config.owner = deps.api.addr_canonicalize(new_owner.as_str())?;
+                    store_config(deps.storage, &config)
+                },
+            )?)
         }
         ExecuteMsg::RegisterVestingAccounts { vesting_accounts } => {
             assert_owner_privilege(deps.storage, deps.api, info.sender)?;
@@ -65,15 +88,10 @@ fn assert_owner_privilege(storage: &dyn Storage, api: &dyn Api, sender: Addr) ->
 
 pub fn update_config(
     deps: DepsMut,
-    owner: Option<String>,
     psi_token: Option<String>,
     genesis_time: Option<u64>,
 ) -> StdResult<Response> {
     let mut config = read_config(deps.storage)?;
-    if let Some(owner) = owner {
-        config.owner = deps.api.addr_canonicalize(&owner)?;
-    }
-
     if let Some(psi_token) = psi_token {
         config.psi_token = deps.api.addr_canonicalize(&psi_token)?;
     }
@@ -217,6 +235,7 @@ pub fn query_config(deps: Deps) -> StdResult<ConfigResponse> {
     let state = read_config(deps.storage)?;
     let resp = ConfigResponse {
         owner: deps.api.addr_humanize(&state.owner)?.to_string(),
+        pending_owner: may_load_pending_owner(deps.storage)?,
         psi_token: deps.api.addr_humanize(&state.psi_token)?.to_string(),
         genesis_time: state.genesis_time,
     };
@@ -321,6 +340,6 @@ fn test_assert_vesting_schedules() {
 }
 
 #[entry_point]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> StdResult<Response> {
+    migrate_contract(deps, &env, CONTRACT_NAME, CONTRACT_VERSION, MIGRATIONS)
 }
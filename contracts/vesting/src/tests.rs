@@ -1,5 +1,7 @@
 use crate::contract::{execute, instantiate, query};
 use services::common::OrderBy;
+use services::migration::init_contract_version;
+use services::ownership::{OwnershipMsg, PendingOwner};
 use services::vesting::{
     ClaimableAmountResponse, ConfigResponse, ExecuteMsg, InstantiateMsg, QueryMsg, VestingAccount,
     VestingAccountResponse, VestingAccountsResponse, VestingInfo, VestingSchedule,
@@ -7,7 +9,7 @@ use services::vesting::{
 
 use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info};
 use cosmwasm_std::{
-    attr, from_binary, to_binary, CosmosMsg, StdError, SubMsg, Timestamp, Uint128, WasmMsg,
+    attr, from_binary, to_binary, Addr, CosmosMsg, StdError, SubMsg, Timestamp, Uint128, WasmMsg,
 };
 use cw20::Cw20ExecuteMsg;
 
@@ -31,6 +33,7 @@ fn proper_initialization() {
         .unwrap(),
         ConfigResponse {
             owner: "owner".to_string(),
+            pending_owner: None,
             psi_token: "psi_token".to_string(),
             genesis_time: 12345u64,
         }
@@ -50,10 +53,11 @@ fn update_config() {
     let info = mock_info("addr0000", &[]);
     let _res = instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
-    let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner2".to_string()),
-        psi_token: None,
-        genesis_time: None,
+    let msg = ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::ProposeNewOwner {
+            owner: "owner2".to_string(),
+            expires_in: Some(100),
+        },
     };
     let info = mock_info("owner", &[]);
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -64,14 +68,23 @@ fn update_config() {
         )
         .unwrap(),
         ConfigResponse {
-            owner: "owner2".to_string(),
+            owner: "owner".to_string(),
+            pending_owner: Some(PendingOwner {
+                owner: Addr::unchecked("owner2"),
+                expires_at: Some(mock_env().block.time.seconds() + 100),
+            }),
             psi_token: "psi_token".to_string(),
             genesis_time: 12345u64,
         }
     );
 
+    let msg = ExecuteMsg::Ownership {
+        ownership_msg: OwnershipMsg::AcceptOwnership {},
+    };
+    let info = mock_info("owner2", &[]);
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
     let msg = ExecuteMsg::UpdateConfig {
-        owner: Some("owner".to_string()),
         psi_token: None,
         genesis_time: None,
     };
@@ -83,7 +96,6 @@ fn update_config() {
     }
 
     let msg = ExecuteMsg::UpdateConfig {
-        owner: None,
         psi_token: Some("psi_token2".to_string()),
         genesis_time: Some(1u64),
     };
@@ -97,6 +109,7 @@ fn update_config() {
         .unwrap(),
         ConfigResponse {
             owner: "owner2".to_string(),
+            pending_owner: None,
             psi_token: "psi_token2".to_string(),
             genesis_time: 1u64,
         }
@@ -555,3 +568,28 @@ fn claim_with_cliff_equals_end_time() {
         }))],
     );
 }
+
+#[test]
+fn migrate_checks_contract_name() {
+    let mut deps = mock_dependencies(&[]);
+
+    let msg = InstantiateMsg {
+        owner: "owner".to_string(),
+        psi_token: "psi_token".to_string(),
+        genesis_time: 12345u64,
+    };
+    let info = mock_info("addr0000", &[]);
+    crate::contract::instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    crate::contract::migrate(deps.as_mut(), mock_env(), services::vesting::MigrateMsg {}).unwrap();
+
+    init_contract_version(&mut deps.storage, "crates.io:nexus-other", "1.0.0").unwrap();
+    let res = crate::contract::migrate(deps.as_mut(), mock_env(), services::vesting::MigrateMsg {});
+    match res {
+        Err(StdError::GenericErr { msg, .. }) => assert_eq!(
+            msg,
+            "Can not migrate crates.io:nexus-other to crates.io:nexus-vesting"
+        ),
+        _ => panic!("Must return migration error"),
+    }
+}
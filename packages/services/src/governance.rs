@@ -1,5 +1,14 @@
+use crate::airdrop::ExecuteMsg as AirdropExecuteMsg;
 use crate::common::OrderBy;
-use cosmwasm_std::{Binary, Decimal, Uint128};
+use crate::community::{
+    ExecuteMsg as CommunityExecuteMsg, GovernanceMsg as CommunityGovernanceMsg,
+};
+use crate::ownership::{OwnershipMsg, PendingOwner};
+use crate::staking::{ExecuteMsg as StakingExecuteMsg, StakingSchedule};
+use crate::vesting::{ExecuteMsg as VestingExecuteMsg, VestingAccount};
+use cosmwasm_std::{
+    to_binary, BankMsg, Binary, Coin, CosmosMsg, Decimal, StdResult, Uint128, WasmMsg,
+};
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -13,14 +22,21 @@ pub struct InstantiateMsg {
     pub timelock_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub unbonding_period: u64,
+    pub expiration_period: u64,
+    /// Poll categories which do not use the parameters above
+    pub category_params: Vec<PollCategoryParams>,
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     Governance { governance_msg: GovernanceMsg },
+    Guardian { guardian_msg: GuardianMsg },
     Anyone { anyone_msg: AnyoneMsg },
     Yourself { yourself_msg: YourselfMsg },
+    Ownership { ownership_msg: OwnershipMsg },
     Receive(Cw20ReceiveMsg),
 }
 
@@ -41,32 +57,98 @@ pub enum AnyoneMsg {
         vote: VoteOption,
         amount: Uint128,
     },
+    /// Cast vote signed by the voter, can be submitted by anyone
+    CastVoteBySignature {
+        /// secp256k1 public key of the voter, voter address is derived from it
+        public_key: Binary,
+        vote: SignedVote,
+        /// secp256k1 signature of sha256 hash of the vote serialized to JSON
+        signature: Binary,
+    },
+    /// Replace already cast vote while the poll is in progress
+    ChangeVote {
+        poll_id: u64,
+        vote: VoteOption,
+        amount: Uint128,
+    },
+    /// Remove already cast vote while the poll is in progress
+    RetractVote {
+        poll_id: u64,
+    },
     WithdrawVotingTokens {
         amount: Option<Uint128>,
     },
+    /// Move staked tokens which are not locked in polls to another address without unstaking
+    TransferStake {
+        recipient: String,
+        amount: Uint128,
+    },
+    /// Transfer withdrawn tokens which unbonding period is over
+    ClaimUnbonded {},
     EndPoll {
         poll_id: u64,
     },
     ExecutePoll {
         poll_id: u64,
     },
+    /// Cancel own poll which has no votes yet in the voting period and get the deposit back
+    CancelPoll {
+        poll_id: u64,
+    },
+    /// Set Expired status to a passed poll which was not executed in time
+    ExpirePoll {
+        poll_id: u64,
+    },
     SnapshotPoll {
         poll_id: u64,
     },
+    /// Delegate voting power of staked tokens to another address
+    Delegate {
+        delegate: String,
+    },
+    Undelegate {},
+    /// Migrate up to limit polls and stakers stored before 1.1.0,
+    /// other messages are rejected till the migration is finished
+    MigrateBatch {
+        limit: u32,
+    },
 }
 
+#[allow(clippy::large_enum_variant)]
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum GovernanceMsg {
     UpdateConfig {
-        owner: Option<String>,
         quorum: Option<Decimal>,
         threshold: Option<Decimal>,
         voting_period: Option<u64>,
         timelock_period: Option<u64>,
         proposal_deposit: Option<Uint128>,
         snapshot_period: Option<u64>,
+        unbonding_period: Option<u64>,
+        expiration_period: Option<u64>,
+        max_execution_retries: Option<u64>,
+        keeper_reward: Option<Uint128>,
+        max_keeper_reward_per_poll: Option<Uint128>,
+        forfeited_deposit_policy: Option<ForfeitedDepositPolicy>,
+        /// Share of the deposit refunded when quorum is reached but the poll is rejected
+        rejected_deposit_refund: Option<Decimal>,
+        /// Replaces all category parameters
+        category_params: Option<Vec<PollCategoryParams>>,
     },
+    /// Add PSI tokens transferred to the contract to the staked amount
+    AddRewards { amount: Uint128 },
+    /// Add PSI tokens transferred to the contract to the keeper reward pool
+    AddKeeperRewards { amount: Uint128 },
+    /// Set or remove the address which can veto passed polls
+    UpdateGuardian { guardian: Option<String> },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardianMsg {
+    /// Prevent execution of a passed poll or a retry of a failed one
+    VetoPoll { poll_id: u64 },
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -74,7 +156,10 @@ pub enum GovernanceMsg {
 pub enum Cw20HookMsg {
     /// StakeVotingTokens a user can stake their psi token to receive rewards
     /// or to vote on polls
-    StakeVotingTokens {},
+    StakeVotingTokens {
+        /// address credited with the staked tokens, the sender by default
+        recipient: Option<String>,
+    },
     /// CreatePoll need to receive deposit from a proposer
     CreatePoll {
         title: String,
@@ -82,6 +167,15 @@ pub enum Cw20HookMsg {
         link: Option<String>,
         execute_msgs: Option<Vec<PollExecuteMsg>>,
         migrate_msgs: Option<Vec<PollMigrateMsg>>,
+        /// AllOrNothing by default
+        execution_mode: Option<PollExecutionMode>,
+        /// Inferred from the poll messages by default
+        category: Option<PollCategory>,
+        /// Named options to vote for instead of yes/no,
+        /// can not be combined with execute_msgs and migrate_msgs
+        options: Option<Vec<PollOption>>,
+        /// Plurality by default, used only with options
+        winning_rule: Option<WinningRule>,
     },
 }
 
@@ -90,7 +184,69 @@ pub enum Cw20HookMsg {
 pub struct PollExecuteMsg {
     pub order: u64,
     pub contract: String,
-    pub msg: Binary,
+    /// Raw message, used for actions which do not have a typed variant
+    pub msg: Option<Binary>,
+    /// Typed action, converted to the contract message at execution
+    pub action: Option<PollAction>,
+    /// Native tokens of this contract sent with the message
+    #[serde(default)]
+    pub funds: Vec<Coin>,
+}
+
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollAction {
+    /// Spend tokens of the community contract
+    CommunitySpend { recipient: String, amount: Uint128 },
+    /// Add distribution schedules to the staking contract
+    AddStakingSchedules { schedules: Vec<StakingSchedule> },
+    /// Register accounts in the vesting contract
+    RegisterVestingAccounts {
+        vesting_accounts: Vec<VestingAccount>,
+    },
+    /// Governance operation of this contract, e.g. UpdateConfig
+    Governance { governance_msg: GovernanceMsg },
+    /// Register a merkle root in the airdrop contract
+    RegisterMerkleRoot { merkle_root: String },
+    /// Send native tokens of this contract, `contract` is the recipient
+    BankSend { amount: Vec<Coin> },
+}
+
+impl PollAction {
+    /// Message to the target `contract`
+    pub fn to_cosmos_msg(&self, contract: String, funds: Vec<Coin>) -> StdResult<CosmosMsg> {
+        let msg = match self.clone() {
+            PollAction::CommunitySpend { recipient, amount } => {
+                to_binary(&CommunityExecuteMsg::Governance {
+                    governance_msg: CommunityGovernanceMsg::Spend { recipient, amount },
+                })?
+            }
+            PollAction::AddStakingSchedules { schedules } => {
+                to_binary(&StakingExecuteMsg::AddSchedules { schedules })?
+            }
+            PollAction::RegisterVestingAccounts { vesting_accounts } => {
+                to_binary(&VestingExecuteMsg::RegisterVestingAccounts { vesting_accounts })?
+            }
+            PollAction::Governance { governance_msg } => {
+                to_binary(&ExecuteMsg::Governance { governance_msg })?
+            }
+            PollAction::RegisterMerkleRoot { merkle_root } => {
+                to_binary(&AirdropExecuteMsg::RegisterMerkleRoot { merkle_root })?
+            }
+            PollAction::BankSend { amount } => {
+                return Ok(CosmosMsg::Bank(BankMsg::Send {
+                    to_address: contract,
+                    amount,
+                }))
+            }
+        };
+        Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract,
+            msg,
+            funds,
+        }))
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -102,6 +258,112 @@ pub struct PollMigrateMsg {
     pub new_code_id: u64,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SignedVote {
+    pub poll_id: u64,
+    pub vote: VoteOption,
+    pub amount: Uint128,
+    /// must be equal to the vote nonce of the voter
+    pub nonce: u64,
+    pub chain_id: String,
+    /// governance contract address
+    pub contract: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollOption {
+    pub name: String,
+    /// Executed only if this option wins
+    pub execute_msgs: Option<Vec<PollExecuteMsg>>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WinningRule {
+    /// Option with the most votes wins
+    #[default]
+    Plurality,
+    /// Option with the most votes wins if it has at least this share
+    /// of all votes except abstain
+    MinimumShare { share: Decimal },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollCategory {
+    Text,
+    ParameterChange,
+    TreasurySpend,
+    CodeMigration,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollParams {
+    pub quorum: Decimal,
+    pub threshold: Decimal,
+    pub voting_period: u64,
+    pub timelock_period: u64,
+    pub proposal_deposit: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollCategoryParams {
+    pub category: PollCategory,
+    pub params: PollParams,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollExecutionMode {
+    /// Poll fails and all its messages are reverted if any message fails
+    #[default]
+    AllOrNothing,
+    /// Failed messages are skipped and the rest are executed
+    BestEffort,
+}
+
+/// What happens with the part of a deposit which is not refunded
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ForfeitedDepositPolicy {
+    /// Add to the staked tokens as rewards
+    #[default]
+    Stakers,
+    /// Transfer to the community contract
+    Community {
+        contract: String,
+    },
+    Burn,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollMessageKind {
+    Execute,
+    Migrate,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PollMessageStatus {
+    Success,
+    Failed {
+        error: String,
+    },
+    /// Reverted with the rest of the poll messages because of the error
+    Reverted {
+        error: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PollMessageResult {
+    pub kind: PollMessageKind,
+    pub order: u64,
+    pub contract: String,
+    pub status: PollMessageStatus,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
@@ -125,11 +387,56 @@ pub enum QueryMsg {
         limit: Option<u32>,
         order_by: Option<OrderBy>,
     },
+    Delegation {
+        address: String,
+    },
+    Delegators {
+        delegate: String,
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
+    Delegate {
+        address: String,
+    },
+    Unbonding {
+        address: String,
+    },
+    /// Nonce of the next vote signed by the voter
+    VoteNonce {
+        address: String,
+    },
+    /// Polls the address voted in, including finished ones.
+    /// Votes removed by 1.0.0 on withdraw are not included
+    VoterPolls {
+        address: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
+    VoterStats {
+        address: String,
+    },
+    Stakers {
+        start_after: Option<String>,
+        limit: Option<u32>,
+        order_by: Option<OrderBy>,
+    },
+    /// Voting power usable in the poll, or the current one if poll is not set
+    VotingPower {
+        address: String,
+        poll_id: Option<u64>,
+    },
+    /// Voting power of all stakers in polls created at the given block, or the current one
+    TotalVotingPower {
+        at: Option<u64>,
+    },
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
+    pub pending_owner: Option<PendingOwner>,
     pub psi_token: String,
     pub quorum: Decimal,
     pub threshold: Decimal,
@@ -137,13 +444,25 @@ pub struct ConfigResponse {
     pub timelock_period: u64,
     pub proposal_deposit: Uint128,
     pub snapshot_period: u64,
+    pub unbonding_period: u64,
+    pub expiration_period: u64,
+    pub max_execution_retries: u64,
+    pub keeper_reward: Uint128,
+    pub max_keeper_reward_per_poll: Uint128,
+    pub forfeited_deposit_policy: ForfeitedDepositPolicy,
+    pub rejected_deposit_refund: Decimal,
+    pub guardian: Option<String>,
+    pub category_params: Vec<PollCategoryParams>,
 }
 
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 pub struct StateResponse {
     pub poll_count: u64,
     pub total_share: Uint128,
+    pub total_staked: Uint128,
     pub total_deposit: Uint128,
+    pub total_unbonding: Uint128,
+    pub keeper_reward_pool: Uint128,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
@@ -151,6 +470,8 @@ pub struct PollResponse {
     pub id: u64,
     pub creator: String,
     pub status: PollStatus,
+    pub category: PollCategory,
+    pub start_height: u64,
     pub end_time: u64,
     pub title: String,
     pub description: String,
@@ -158,12 +479,29 @@ pub struct PollResponse {
     pub deposit_amount: Uint128,
     pub execute_data: Option<Vec<PollExecuteMsg>>,
     pub migrate_data: Option<Vec<PollMigrateMsg>>,
-    pub yes_votes: Uint128, // balance
-    pub no_votes: Uint128,  // balance
+    pub execution_mode: PollExecutionMode,
+    /// results of executed messages in execution order
+    pub execution_results: Vec<PollMessageResult>,
+    pub execution_retries: u64,
+    pub keeper_reward_paid: Uint128,
+    pub yes_votes: Uint128,     // balance
+    pub no_votes: Uint128,      // balance
+    pub abstain_votes: Uint128, // balance
+    pub options: Vec<PollOptionResponse>,
+    pub winning_rule: WinningRule,
+    /// index of the winning option of a passed multi-choice poll
+    pub winning_option: Option<u32>,
     pub staked_amount: Option<Uint128>,
     pub total_balance_at_end_poll: Option<Uint128>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct PollOptionResponse {
+    pub name: String,
+    pub execute_msgs: Option<Vec<PollExecuteMsg>>,
+    pub votes: Uint128, // balance
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct PollsResponse {
     pub polls: Vec<PollResponse>,
@@ -181,6 +519,31 @@ pub struct StakerResponse {
     pub locked_balance: Vec<(u64, VoterInfo)>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakersResponseItem {
+    pub staker: String,
+    pub share: Uint128,
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct StakersResponse {
+    pub stakers: Vec<StakersResponseItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct VotingPowerResponse {
+    pub own_balance: Uint128,
+    pub delegated_balance: Uint128,
+    pub voting_power: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct TotalVotingPowerResponse {
+    pub total_share: Uint128,
+    pub total_balance: Uint128,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
 pub struct VotersResponseItem {
     pub voter: String,
@@ -193,6 +556,68 @@ pub struct VotersResponse {
     pub voters: Vec<VotersResponseItem>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct VoterPollsResponseItem {
+    pub poll_id: u64,
+    pub vote: VoteOption,
+    pub balance: Uint128,
+    pub status: PollStatus,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct VoterPollsResponse {
+    pub polls: Vec<VoterPollsResponseItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct VoterStatsResponse {
+    pub polls_voted: u64,
+    /// Sum of vote amounts in all polls
+    pub total_weight: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegationResponse {
+    pub delegate: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegatorsResponseItem {
+    pub delegator: String,
+    pub share: Uint128,
+    pub balance: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegatorsResponse {
+    pub delegators: Vec<DelegatorsResponseItem>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct DelegateResponse {
+    pub delegated_share: Uint128,
+    pub delegated_balance: Uint128,
+    /// own staked balance plus delegated balance
+    pub voting_power: Uint128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct UnbondingResponse {
+    pub entries: Vec<UnbondingEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, JsonSchema)]
+pub struct VoteNonceResponse {
+    pub nonce: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct UnbondingEntry {
+    pub amount: Uint128,
+    /// tokens can be claimed after this time
+    pub release_time: u64,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct VoterInfo {
     pub vote: VoteOption,
@@ -207,6 +632,9 @@ pub enum PollStatus {
     Rejected,
     Executed,
     Failed,
+    Expired,
+    Cancelled,
+    Vetoed,
 }
 
 impl fmt::Display for PollStatus {
@@ -220,17 +648,40 @@ impl fmt::Display for PollStatus {
 pub enum VoteOption {
     Yes,
     No,
+    Abstain,
+    /// Vote for the option of a multi-choice poll by its index
+    Choice {
+        option: u32,
+    },
+    /// Split the vote amount across options by weights which sum to one
+    Split {
+        allocations: Vec<WeightedVoteOption>,
+    },
 }
 
 impl fmt::Display for VoteOption {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if *self == VoteOption::Yes {
-            write!(f, "yes")
-        } else {
-            write!(f, "no")
+        match self {
+            VoteOption::Yes => write!(f, "yes"),
+            VoteOption::No => write!(f, "no"),
+            VoteOption::Abstain => write!(f, "abstain"),
+            VoteOption::Choice { option } => write!(f, "option_{}", option),
+            VoteOption::Split { allocations } => {
+                let allocations: Vec<String> = allocations
+                    .iter()
+                    .map(|allocation| format!("{}:{}", allocation.option, allocation.weight))
+                    .collect();
+                write!(f, "{}", allocations.join(","))
+            }
         }
     }
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct WeightedVoteOption {
+    pub option: VoteOption,
+    pub weight: Decimal,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct MigrateMsg {}
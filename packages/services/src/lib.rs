@@ -2,5 +2,7 @@ pub mod airdrop;
 pub mod common;
 pub mod community;
 pub mod governance;
+pub mod migration;
+pub mod ownership;
 pub mod staking;
 pub mod vesting;
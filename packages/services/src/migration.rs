@@ -0,0 +1,71 @@
+use cosmwasm_std::{DepsMut, Env, Response, StdError, StdResult, Storage};
+use cw2::{get_contract_version, set_contract_version};
+use semver::Version;
+
+/// State migration of the contract release
+pub type MigrationFn = fn(DepsMut, &Env) -> StdResult<()>;
+
+/// Stores cw2 contract name and version, should be called at instantiate
+pub fn init_contract_version(
+    storage: &mut dyn Storage,
+    name: &str,
+    version: &str,
+) -> StdResult<()> {
+    set_contract_version(storage, name, version)
+}
+
+/// Checks the stored contract name and version and runs state migrations of all
+/// releases newer than the stored one. `migrations` are (version, migration) pairs
+/// sorted by version. Contracts instantiated without cw2 metadata run all migrations.
+pub fn migrate_contract(
+    mut deps: DepsMut,
+    env: &Env,
+    name: &str,
+    version: &str,
+    migrations: &[(&str, MigrationFn)],
+) -> StdResult<Response> {
+    let new_version = parse_version(version)?;
+    let stored_version = match get_contract_version(deps.storage) {
+        Ok(stored) => {
+            if stored.contract != name {
+                return Err(StdError::generic_err(format!(
+                    "Can not migrate {} to {}",
+                    stored.contract, name
+                )));
+            }
+            let stored_version = parse_version(&stored.version)?;
+            if stored_version > new_version {
+                return Err(StdError::generic_err(format!(
+                    "Can not downgrade {} from {} to {}",
+                    name, stored.version, version
+                )));
+            }
+            Some(stored_version)
+        }
+        Err(_) => None,
+    };
+
+    for (migration_version, migration) in migrations {
+        let migration_version = parse_version(migration_version)?;
+        let is_applied = matches!(&stored_version, Some(stored) if *stored >= migration_version);
+        if !is_applied && migration_version <= new_version {
+            migration(deps.branch(), env)?;
+        }
+    }
+
+    set_contract_version(deps.storage, name, version)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "migrate"),
+        (
+            "previous_version",
+            &stored_version.map_or_else(|| "none".to_string(), |v| v.to_string()),
+        ),
+        ("new_version", version),
+    ]))
+}
+
+fn parse_version(version: &str) -> StdResult<Version> {
+    Version::parse(version)
+        .map_err(|_| StdError::generic_err(format!("Invalid contract version: {}", version)))
+}
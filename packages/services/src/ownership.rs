@@ -0,0 +1,157 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{Addr, DepsMut, Env, MessageInfo, Response, StdError, StdResult, Storage};
+use cw_storage_plus::Item;
+use thiserror::Error;
+
+static PENDING_OWNER: Item<PendingOwner> = Item::new("pending_owner");
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum OwnershipMsg {
+    /// Owner operation, the proposed owner has to accept the ownership before it expires
+    ProposeNewOwner {
+        owner: String,
+        /// proposal lifetime in seconds, never expires if not set
+        expires_in: Option<u64>,
+    },
+    /// Owner operation to remove the pending proposal
+    CancelOwnershipProposal {},
+    /// Proposed owner operation to take over the contract
+    AcceptOwnership {},
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingOwner {
+    pub owner: Addr,
+    /// proposal can not be accepted after this time
+    pub expires_at: Option<u64>,
+}
+
+#[derive(Error, Debug, PartialEq)]
+pub enum OwnershipError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("unauthorized")]
+    Unauthorized,
+
+    #[error("No ownership proposal")]
+    NoOwnershipProposal,
+
+    #[error("Ownership proposal expired")]
+    OwnershipProposalExpired,
+}
+
+// contracts without own error type get the error message
+impl From<OwnershipError> for StdError {
+    fn from(error: OwnershipError) -> Self {
+        match error {
+            OwnershipError::Std(error) => error,
+            error => StdError::generic_err(error.to_string()),
+        }
+    }
+}
+
+pub fn may_load_pending_owner(storage: &dyn Storage) -> StdResult<Option<PendingOwner>> {
+    PENDING_OWNER.may_load(storage)
+}
+
+/// Handles the ownership message of a contract with the given owner,
+/// `store_owner` is called with the new owner when the proposal is accepted.
+pub fn execute_ownership_msg<F>(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    owner: &Addr,
+    msg: OwnershipMsg,
+    store_owner: F,
+) -> Result<Response, OwnershipError>
+where
+    F: FnOnce(DepsMut, &Addr) -> StdResult<()>,
+{
+    match msg {
+        OwnershipMsg::ProposeNewOwner {
+            owner: new_owner,
+            expires_in,
+        } => {
+            assert_owner(info, owner)?;
+            propose_new_owner(deps, env, new_owner, expires_in)
+        }
+        OwnershipMsg::CancelOwnershipProposal {} => {
+            assert_owner(info, owner)?;
+            cancel_ownership_proposal(deps.storage)
+        }
+        OwnershipMsg::AcceptOwnership {} => accept_ownership(deps, env, info, store_owner),
+    }
+}
+
+fn assert_owner(info: &MessageInfo, owner: &Addr) -> Result<(), OwnershipError> {
+    if &info.sender != owner {
+        return Err(OwnershipError::Unauthorized);
+    }
+
+    Ok(())
+}
+
+fn propose_new_owner(
+    deps: DepsMut,
+    env: &Env,
+    new_owner: String,
+    expires_in: Option<u64>,
+) -> Result<Response, OwnershipError> {
+    let pending_owner = PendingOwner {
+        owner: deps.api.addr_validate(&new_owner)?,
+        expires_at: expires_in.map(|expires_in| env.block.time.seconds() + expires_in),
+    };
+    PENDING_OWNER.save(deps.storage, &pending_owner)?;
+
+    let mut response = Response::new().add_attributes(vec![
+        ("action", "propose_new_owner"),
+        ("new_owner", pending_owner.owner.as_str()),
+    ]);
+    if let Some(expires_at) = pending_owner.expires_at {
+        response = response.add_attribute("expires_at", expires_at.to_string());
+    }
+    Ok(response)
+}
+
+fn cancel_ownership_proposal(storage: &mut dyn Storage) -> Result<Response, OwnershipError> {
+    if PENDING_OWNER.may_load(storage)?.is_none() {
+        return Err(OwnershipError::NoOwnershipProposal);
+    }
+    PENDING_OWNER.remove(storage);
+
+    Ok(Response::new().add_attribute("action", "cancel_ownership_proposal"))
+}
+
+fn accept_ownership<F>(
+    deps: DepsMut,
+    env: &Env,
+    info: &MessageInfo,
+    store_owner: F,
+) -> Result<Response, OwnershipError>
+where
+    F: FnOnce(DepsMut, &Addr) -> StdResult<()>,
+{
+    let pending_owner = PENDING_OWNER
+        .may_load(deps.storage)?
+        .ok_or(OwnershipError::NoOwnershipProposal)?;
+    if info.sender != pending_owner.owner {
+        return Err(OwnershipError::Unauthorized);
+    }
+    if let Some(expires_at) = pending_owner.expires_at {
+        if env.block.time.seconds() > expires_at {
+            return Err(OwnershipError::OwnershipProposalExpired);
+        }
+    }
+
+    PENDING_OWNER.remove(deps.storage);
+    store_owner(deps, &pending_owner.owner)?;
+
+    Ok(Response::new().add_attributes(vec![
+        ("action", "accept_ownership"),
+        ("new_owner", pending_owner.owner.as_str()),
+    ]))
+}
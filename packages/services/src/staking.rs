@@ -1,6 +1,7 @@
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::ownership::{OwnershipMsg, PendingOwner};
 use cosmwasm_std::{Decimal, Uint128};
 use cw20::Cw20ReceiveMsg;
 use terraswap::asset::Asset;
@@ -37,8 +38,8 @@ pub enum ExecuteMsg {
     AddSchedules {
         schedules: Vec<StakingSchedule>,
     },
-    UpdateOwner {
-        owner: String,
+    Ownership {
+        ownership_msg: OwnershipMsg,
     },
     /// Owner operation to stop distribution on current staking contract
     /// and send remaining tokens to the new contract
@@ -86,6 +87,7 @@ pub enum QueryMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
+    pub pending_owner: Option<PendingOwner>,
     pub psi_token: String,
     pub staking_token: String,
     pub terraswap_factory: String,
@@ -2,6 +2,7 @@ use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
 use crate::common::OrderBy;
+use crate::ownership::{OwnershipMsg, PendingOwner};
 use cosmwasm_std::Uint128;
 
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
@@ -15,10 +16,12 @@ pub struct InstantiateMsg {
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     UpdateConfig {
-        owner: Option<String>,
         psi_token: Option<String>,
         genesis_time: Option<u64>,
     },
+    Ownership {
+        ownership_msg: OwnershipMsg,
+    },
     RegisterVestingAccounts {
         vesting_accounts: Vec<VestingAccount>,
     },
@@ -80,6 +83,7 @@ pub enum QueryMsg {
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
 pub struct ConfigResponse {
     pub owner: String,
+    pub pending_owner: Option<PendingOwner>,
     pub psi_token: String,
     pub genesis_time: u64,
 }